@@ -207,8 +207,11 @@ export class AuthService {
             passwordHash
           );
 
-          if (result.isOk && output) {
-            const accountId = output.toHuman() as string;
+          // Only a completed login yields an account; TwoFactorRequired and
+          // Failed outcomes fall through to the database path
+          const outcome = (output?.toHuman() as any)?.Ok;
+          const accountId = outcome?.Success as string | undefined;
+          if (result.isOk && accountId) {
 
             // Get user from database
             let { data: user } = await supabaseAdmin
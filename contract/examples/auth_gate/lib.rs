@@ -0,0 +1,200 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+// ink!'s generated storage code trips this lint on AccountId fields
+#![allow(clippy::clone_on_copy)]
+
+/// Example consumer of the `AuthentifyQuery` trait
+///
+/// Lets verified Authentify users with an active session through the gate
+/// without duplicating any identity logic.
+#[ink::contract]
+mod auth_gate {
+    use authentify_contract::AuthentifyQuery;
+    use ink::prelude::string::String;
+
+    #[ink(storage)]
+    pub struct AuthGate {
+        /// Authentify contract being queried
+        authentify: ink::contract_ref!(AuthentifyQuery),
+        /// Authentify client the gate accepts sessions for; `None` for unscoped sessions
+        client_id: Option<u64>,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// Session is unknown, revoked, expired, issued to another client or owned by
+        /// someone else
+        InvalidSession,
+        /// Caller has no valid verification
+        NotVerified,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct Entered {
+        #[ink(topic)]
+        account: AccountId,
+        username: String,
+    }
+
+    impl AuthGate {
+        #[ink(constructor)]
+        pub fn new(authentify: AccountId, client_id: Option<u64>) -> Self {
+            Self {
+                authentify: authentify.into(),
+                client_id,
+            }
+        }
+
+        /// Check if an account is a verified Authentify user
+        #[ink(message)]
+        pub fn is_member(&self, account: AccountId) -> bool {
+            self.authentify.has_identity(account) && self.authentify.is_verified(account)
+        }
+
+        /// Pass the gate with one of the caller's Authentify sessions
+        ///
+        /// # Returns
+        /// * `Ok(String)` - Username of the caller
+        #[ink(message)]
+        pub fn enter(&mut self, session_id: String) -> Result<String> {
+            let caller = self.env().caller();
+
+            if self.authentify.verify_session(session_id, self.client_id) != Some(caller) {
+                return Err(Error::InvalidSession);
+            }
+
+            if !self.authentify.is_verified(caller) {
+                return Err(Error::NotVerified);
+            }
+
+            let username = self.authentify.username_of(caller).unwrap_or_default();
+
+            self.env().emit_event(Entered {
+                account: caller,
+                username: username.clone(),
+            });
+
+            Ok(username)
+        }
+    }
+
+    // ========================================
+    // E2E TESTS
+    // ========================================
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use authentify_contract::authentify::{Error as AuthentifyError, VerificationLevel};
+        use authentify_contract::{Authentify, AuthentifyRef};
+        use ink_e2e::{account_id, AccountKeyring, ContractsBackend};
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn gate_checks_identity_and_session<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            let alice = account_id(AccountKeyring::Alice);
+            let session_id = String::from("session_123");
+
+            // Deploy Authentify; Alice is admin
+            let mut constructor = AuthentifyRef::new();
+            let authentify = client
+                .instantiate("authentify-contract", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("authentify instantiate failed");
+            let mut call_builder = authentify.call_builder::<Authentify>();
+
+            client
+                .call(&ink_e2e::alice(), &call_builder.set_direct_registration(true))
+                .submit()
+                .await
+                .expect("set_direct_registration failed");
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.register_identity(
+                        String::from("alice"),
+                        String::from("valid_password_hash"),
+                        String::from("social_hash"),
+                        String::from("google"),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("register_identity failed");
+            client
+                .call(&ink_e2e::alice(), &call_builder.create_session(alice, session_id.clone(), 600_000, None))
+                .submit()
+                .await
+                .expect("create_session failed");
+
+            // Deploy the gate pointing at Authentify
+            let mut constructor = AuthGateRef::new(authentify.account_id, None);
+            let gate = client
+                .instantiate("auth-gate", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("auth-gate instantiate failed");
+            let mut gate_call = gate.call_builder::<AuthGate>();
+
+            // Registered but not yet verified
+            let is_member = client
+                .call(&ink_e2e::alice(), &gate_call.is_member(alice))
+                .dry_run()
+                .await?;
+            assert!(!is_member.return_value());
+            let entered = client
+                .call(&ink_e2e::alice(), &gate_call.enter(session_id.clone()))
+                .dry_run()
+                .await?;
+            assert_eq!(entered.return_value(), Err(Error::NotVerified));
+
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.verify_identity(alice, VerificationLevel::Email, [1u8; 32], None),
+                )
+                .submit()
+                .await
+                .expect("verify_identity failed");
+
+            let is_member = client
+                .call(&ink_e2e::alice(), &gate_call.is_member(alice))
+                .dry_run()
+                .await?;
+            assert!(is_member.return_value());
+            let entered = client
+                .call(&ink_e2e::alice(), &gate_call.enter(session_id.clone()))
+                .submit()
+                .await
+                .expect("enter failed");
+            assert_eq!(entered.return_value(), Ok(String::from("alice")));
+
+            // Someone else's session does not open the gate
+            let entered = client
+                .call(&ink_e2e::bob(), &gate_call.enter(session_id))
+                .dry_run()
+                .await?;
+            assert_eq!(entered.return_value(), Err(Error::InvalidSession));
+
+            // Nor can anyone else mint a session for alice and get her through
+            let forged = client
+                .call(&ink_e2e::bob(), &call_builder.create_session(alice, String::from("forged"), 600_000, None))
+                .dry_run()
+                .await?;
+            assert_eq!(forged.return_value(), Err(AuthentifyError::Unauthorized));
+            let entered = client
+                .call(&ink_e2e::alice(), &gate_call.enter(String::from("forged")))
+                .dry_run()
+                .await?;
+            assert_eq!(entered.return_value(), Err(Error::InvalidSession));
+
+            Ok(())
+        }
+    }
+}
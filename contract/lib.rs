@@ -1,10 +1,170 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+mod webauthn;
+
+pub use self::authentify::{Authentify, AuthentifyRef};
+
+use ink::prelude::string::String;
+use ink::primitives::AccountId;
+
+/// Read-only view of Authentify for other ink! contracts
+///
+/// Depend on this crate with the `ink-as-dependency` feature and call through
+/// `ink::contract_ref!(AuthentifyQuery)`.
+#[ink::trait_definition]
+pub trait AuthentifyQuery {
+    /// Check if an account has a registered identity
+    #[ink(message)]
+    fn has_identity(&self, account: AccountId) -> bool;
+
+    /// Check if an account holds a verification that has not expired
+    #[ink(message)]
+    fn is_verified(&self, account: AccountId) -> bool;
+
+    /// Get the account of an active, unexpired session issued to `client_id`
+    ///
+    /// Pass `None` to accept only sessions that are not scoped to a client.
+    #[ink(message)]
+    fn verify_session(&self, session_id: String, client_id: Option<u64>) -> Option<AccountId>;
+
+    /// Get the username of an account
+    #[ink(message)]
+    fn username_of(&self, account: AccountId) -> Option<String>;
+}
+
 #[ink::contract]
-mod authentify {
-    use ink::storage::Mapping;
+// The call builder ink! generates clones its `AccountId` under our storage span
+#[allow(clippy::clone_on_copy)]
+pub mod authentify {
+    use ink::storage::{Mapping, StorageVec};
     use ink::storage::traits::ManualKey;
     use ink::prelude::string::String;
+    use ink::prelude::vec;
+    use ink::prelude::vec::Vec;
+    use core::ops::RangeInclusive;
+    use ink::env::hash::{Blake2x256, Sha2x256};
+    use crate::webauthn::{self, WebAuthnError};
+    use crate::AuthentifyQuery;
+
+    /// Default `max_failed_attempts` used by `new`
+    pub const DEFAULT_MAX_FAILED_ATTEMPTS: u32 = 5;
+
+    /// Default `lockout_duration` used by `new` (15 minutes in milliseconds)
+    pub const DEFAULT_LOCKOUT_DURATION: u64 = 900000;
+
+    /// Accepted values for `max_failed_attempts`
+    pub const MAX_FAILED_ATTEMPTS_RANGE: RangeInclusive<u32> = 3..=20;
+
+    /// Accepted values for `lockout_duration` (1 minute to 30 days in milliseconds)
+    pub const LOCKOUT_DURATION_RANGE: RangeInclusive<u64> = 60000..=2592000000;
+
+    /// Default `reveal_window` used by `new` (~10 minutes at 6s blocks)
+    pub const DEFAULT_REVEAL_WINDOW: BlockNumber = 100;
+
+    /// Accepted values for `reveal_window` (1 block to ~1 day at 6s blocks)
+    pub const REVEAL_WINDOW_RANGE: RangeInclusive<BlockNumber> = 1..=14400;
+
+    /// Default `two_factor_window` used by `new` (5 minutes in milliseconds)
+    pub const DEFAULT_TWO_FACTOR_WINDOW: u64 = 300000;
+
+    /// Accepted values for `two_factor_window` (30 seconds to 1 hour in milliseconds)
+    pub const TWO_FACTOR_WINDOW_RANGE: RangeInclusive<u64> = 30000..=3600000;
+
+    /// Largest accepted `registration_deposit` and `session_deposit` (1,000 units at 12 decimals)
+    pub const MAX_DEPOSIT: Balance = 1_000_000_000_000_000;
+
+    /// Maximum number of items returned by paginated queries
+    pub const MAX_PAGE_SIZE: u32 = 50;
+
+    /// Maximum number of index entries examined by one filtered listing
+    pub const MAX_SCAN_SIZE: u32 = 200;
+
+    /// Number of leading characters that select a username search bucket
+    pub const USERNAME_PREFIX_LEN: usize = 3;
+
+    /// Maximum number of items accepted by batch messages
+    pub const MAX_BATCH_SIZE: usize = 50;
+
+    /// Maximum number of social providers on the allowlist
+    pub const MAX_PROVIDERS: u32 = 16;
+
+    /// Maximum length of a social provider name
+    pub const MAX_PROVIDER_LEN: usize = 32;
+
+    /// Social providers allowed at deployment
+    pub const DEFAULT_PROVIDERS: [&str; 4] = ["google", "github", "twitter", "email"];
+
+    /// Number of audit entries kept per account; older ones are overwritten
+    pub const MAX_AUDIT_ENTRIES: u32 = 32;
+
+    /// Maximum number of additional verification methods per DID
+    pub const MAX_DID_KEYS: u32 = 10;
+
+    /// Maximum number of service endpoints per DID
+    pub const MAX_DID_SERVICES: u32 = 10;
+
+    /// Maximum length of a DID verification method's public key in bytes
+    pub const MAX_DID_PUBLIC_KEY_LEN: usize = 128;
+
+    /// Maximum length of a DID service type
+    pub const MAX_DID_SERVICE_TYPE_LEN: usize = 64;
+
+    /// Maximum length of a DID service endpoint
+    pub const MAX_DID_ENDPOINT_LEN: usize = 256;
+
+    /// Method prefix of Authentify DIDs
+    pub const DID_PREFIX: &str = "did:authentify:";
+
+    /// Maximum length of a credential type
+    pub const MAX_CREDENTIAL_TYPE_LEN: usize = 64;
+
+    /// Maximum number of passkeys per identity
+    pub const MAX_PASSKEYS: u32 = 10;
+
+    /// Maximum length of a WebAuthn credential ID in bytes
+    pub const MAX_CREDENTIAL_ID_LEN: usize = 255;
+
+    /// Maximum length of a passkey label
+    pub const MAX_PASSKEY_LABEL_LEN: usize = 64;
+
+    /// Number of skipped one-time codes tolerated when verifying a second-factor proof
+    pub const TWO_FACTOR_LOOKAHEAD: u32 = 10;
+
+    /// Maximum number of recovery codes stored per account
+    pub const MAX_RECOVERY_CODES: usize = 16;
+
+    /// Maximum number of unrevoked sessions per account
+    pub const MAX_SESSIONS_PER_ACCOUNT: usize = 20;
+
+    /// Maximum number of session keys per account
+    pub const MAX_SESSION_KEYS: usize = 10;
+
+    /// Maximum number of permission scopes per session key
+    pub const MAX_SCOPES: usize = 8;
+
+    /// Maximum length of a permission scope
+    pub const MAX_SCOPE_LEN: usize = 32;
+
+    /// Maximum length of a client application name
+    pub const MAX_CLIENT_NAME_LEN: usize = 64;
+
+    /// Maximum number of redirect URIs per client application
+    pub const MAX_REDIRECT_URIS: usize = 10;
+
+    /// Maximum length of a redirect URI
+    pub const MAX_REDIRECT_URI_LEN: usize = 256;
+
+    /// Maximum number of client applications one account can register
+    pub const MAX_CLIENTS_PER_OWNER: u32 = 5;
+
+    /// Maximum number of client applications an account can consent to
+    pub const MAX_CONSENTS: usize = 20;
+
+    /// Maximum number of profile attributes per account
+    pub const MAX_ATTRIBUTES: usize = 20;
+
+    /// Maximum length of a profile attribute key
+    pub const MAX_ATTRIBUTE_KEY_LEN: usize = 32;
 
     /// Represents a user's complete identity information
     #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
@@ -23,8 +183,6 @@ mod authentify {
         pub social_provider: String,
         /// The wallet address that owns this identity
         pub wallet_address: AccountId,
-        /// Whether identity has been verified by admin
-        pub is_verified: bool,
         /// Timestamp when identity was created
         pub created_at: u64,
         /// Timestamp of last login attempt
@@ -33,6 +191,8 @@ mod authentify {
         pub failed_attempts: u32,
         /// Whether account is locked due to too many failed attempts
         pub is_locked: bool,
+        /// Whether login requires a second-factor proof after the password
+        pub two_factor_enabled: bool,
     }
 
     /// Session information for active users
@@ -47,6 +207,520 @@ mod authentify {
         pub created_at: u64,
         pub expires_at: u64,
         pub is_active: bool,
+        /// Client application the session was issued to (audience)
+        pub client_id: Option<u64>,
+    }
+
+    /// Salted hash commitment to a profile attribute value
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct AttributeCommitment {
+        /// Attribute name, e.g. `"email"` or `"country"`
+        pub key: String,
+        /// Blake2x256 hash of (account, key, value, salt)
+        pub commitment: [u8; 32],
+        pub updated_at: u64,
+    }
+
+    /// Public view of an identity and its profile attribute commitments
+    ///
+    /// Leaves out `password_hash`, `social_id_hash`, `failed_attempts` and `last_login`.
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PublicIdentity {
+        pub username: String,
+        pub social_provider: String,
+        pub wallet_address: AccountId,
+        /// Holds a verification that has not expired
+        pub is_verified: bool,
+        pub created_at: u64,
+        pub is_locked: bool,
+        pub two_factor_enabled: bool,
+        pub attributes: Vec<AttributeCommitment>,
+    }
+
+    /// Security-relevant action recorded in an account's audit log
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum AuditAction {
+        LoginSucceeded,
+        LoginFailed(LoginFailureReason),
+        AccountLocked,
+        AccountUnlocked,
+        PasswordChanged,
+        SessionCreated,
+        SessionRevoked,
+        TwoFactorEnabled,
+        TwoFactorDisabled,
+        RecoveryCodeUsed(RecoveryAction),
+        PasskeyAdded,
+        PasskeyRemoved,
+    }
+
+    /// One entry of an account's audit log
+    #[derive(Debug, Clone, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct AuditEntry {
+        pub action: AuditAction,
+        /// Account that made the call
+        pub actor: AccountId,
+        pub timestamp: u64,
+    }
+
+    /// Admin-tunable settings, validated as a whole
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Config {
+        /// Maximum failed login attempts before lockout
+        pub max_failed_attempts: u32,
+        /// Lockout duration in milliseconds
+        pub lockout_duration: u64,
+        /// Number of blocks after a commitment during which it can be revealed
+        pub reveal_window: BlockNumber,
+        /// Time allowed between password step and second-factor proof in milliseconds
+        pub two_factor_window: u64,
+        /// Whether the single-call `register_identity` path is enabled
+        pub direct_registration_enabled: bool,
+        /// Deposit required to register an identity
+        pub registration_deposit: Balance,
+        /// Deposit required to create a session
+        pub session_deposit: Balance,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                max_failed_attempts: DEFAULT_MAX_FAILED_ATTEMPTS,
+                lockout_duration: DEFAULT_LOCKOUT_DURATION,
+                reveal_window: DEFAULT_REVEAL_WINDOW,
+                two_factor_window: DEFAULT_TWO_FACTOR_WINDOW,
+                direct_registration_enabled: false,
+                registration_deposit: 0,
+                session_deposit: 0,
+            }
+        }
+    }
+
+    impl Config {
+        /// Check every setting against its accepted range
+        pub fn validate(&self) -> Result<()> {
+            if !MAX_FAILED_ATTEMPTS_RANGE.contains(&self.max_failed_attempts) {
+                return Err(Error::InvalidMaxFailedAttempts);
+            }
+
+            if !LOCKOUT_DURATION_RANGE.contains(&self.lockout_duration) {
+                return Err(Error::InvalidLockoutDuration);
+            }
+
+            if !REVEAL_WINDOW_RANGE.contains(&self.reveal_window) {
+                return Err(Error::InvalidRevealWindow);
+            }
+
+            if !TWO_FACTOR_WINDOW_RANGE.contains(&self.two_factor_window) {
+                return Err(Error::InvalidTwoFactorWindow);
+            }
+
+            if self.registration_deposit > MAX_DEPOSIT || self.session_deposit > MAX_DEPOSIT {
+                return Err(Error::InvalidDeposit);
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Contract-wide counters for monitoring
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractStats {
+        pub total_users: u64,
+        pub active_sessions: u64,
+        /// Cumulative verifications granted (including re-verifications)
+        pub verifications_granted: u64,
+        /// Cumulative lockouts after too many failed logins
+        pub lockouts: u64,
+        pub total_logins: u64,
+        pub failed_logins: u64,
+        pub sessions_created: u64,
+        pub sessions_revoked: u64,
+        /// Cumulative registrations for each allowed provider
+        pub registrations_by_provider: Vec<(String, u64)>,
+    }
+
+    /// Criteria for `list_identities`; `None` fields match everything
+    #[derive(Debug, Clone, Default, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct IdentityFilter {
+        pub verified: Option<bool>,
+        pub locked: Option<bool>,
+        pub provider: Option<String>,
+    }
+
+    /// One page of `list_identities`
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct IdentityPage {
+        pub identities: Vec<PublicIdentity>,
+        /// Offset to continue from, `None` once the index is exhausted
+        pub next_offset: Option<u32>,
+    }
+
+    /// Scopes an account agreed to share with a client application
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ConsentGrant {
+        pub client_id: u64,
+        pub scopes: Vec<String>,
+        pub granted_at: u64,
+        pub expires_at: u64,
+    }
+
+    /// OAuth-style client application allowed to request sessions
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ClientApp {
+        pub client_id: u64,
+        pub owner: AccountId,
+        pub name: String,
+        pub redirect_uris: Vec<String>,
+        pub enabled: bool,
+        pub created_at: u64,
+    }
+
+    /// Delegated key allowed to act for its owner within a set of scopes
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SessionKey {
+        /// Delegated account
+        pub key: AccountId,
+        /// Permission scopes granted to the key
+        pub scopes: Vec<String>,
+        pub created_at: u64,
+        pub expires_at: u64,
+        /// Session the key is bound to; revoking the session revokes the key
+        pub session_id: Option<String>,
+    }
+
+    /// Pending commitment for the commit-reveal registration flow
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RegistrationCommitment {
+        /// Blake2x256 hash of (username, salt, account)
+        pub commitment: [u8; 32],
+        /// Block number at which the commitment was made
+        pub committed_at: BlockNumber,
+    }
+
+    /// Refundable deposit held for a session
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SessionDeposit {
+        /// Account that paid the deposit and receives the refund
+        pub payer: AccountId,
+        /// Deposited amount
+        pub amount: Balance,
+    }
+
+    /// Verification tiers, ordered from weakest to strongest
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum VerificationLevel {
+        /// Email address confirmed
+        Email,
+        /// Lightweight KYC (e.g. document check)
+        KycLite,
+        /// Full KYC
+        KycFull,
+    }
+
+    /// Details of an identity verification
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct VerificationRecord {
+        /// Verification tier granted
+        pub level: VerificationLevel,
+        /// Account that performed the verification
+        pub verified_by: AccountId,
+        /// Timestamp of the verification
+        pub verified_at: u64,
+        /// Hash of the off-chain evidence the verification is based on
+        pub evidence_hash: [u8; 32],
+        /// Optional timestamp after which the verification is no longer valid
+        pub expires_at: Option<u64>,
+    }
+
+    /// Lifecycle state of a verification request
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum RequestStatus {
+        Pending,
+        Approved,
+        Rejected,
+        /// Identity was deleted, or verified directly at a different level,
+        /// while the request was pending
+        Cancelled,
+    }
+
+    /// User-submitted request to be verified at a given level
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct VerificationRequest {
+        pub request_id: u64,
+        /// Account asking to be verified
+        pub account: AccountId,
+        /// Requested verification tier
+        pub level: VerificationLevel,
+        /// Hash of the off-chain evidence submitted
+        pub evidence_hash: [u8; 32],
+        pub status: RequestStatus,
+        pub created_at: u64,
+        /// Verifier that approved or rejected the request
+        pub resolved_by: Option<AccountId>,
+        pub resolved_at: Option<u64>,
+        /// Reason given on rejection
+        pub reason: Option<String>,
+    }
+
+    /// Salted hashes of an account's unused recovery codes
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RecoveryCodes {
+        /// Random per-account salt mixed into every hash
+        pub salt: [u8; 32],
+        /// `compute_recovery_code_hash` of each unused code
+        pub hashes: Vec<[u8; 32]>,
+    }
+
+    /// What a consumed recovery code is used for
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum RecoveryAction {
+        /// Turn off the lost second factor
+        ResetTwoFactor,
+        /// Unlock an account locked by failed attempts
+        UnlockAccount,
+    }
+
+    /// Login waiting for its second-factor proof
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingLogin {
+        /// Timestamp at which the password step succeeded
+        pub created_at: u64,
+        /// Second-factor proof must arrive before this timestamp
+        pub expires_at: u64,
+    }
+
+    /// WebAuthn/passkey credential bound to an identity
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PasskeyCredential {
+        /// WebAuthn credential ID (raw bytes)
+        pub credential_id: Vec<u8>,
+        /// COSE-encoded ES256 (P-256) public key
+        pub public_key: Vec<u8>,
+        /// SHA-256 of the relying party ID the credential is scoped to
+        pub rp_id_hash: [u8; 32],
+        /// Last signature counter reported by the authenticator
+        pub sign_count: u32,
+        /// User-facing label (e.g. "YubiKey", "iPhone")
+        pub label: String,
+        pub created_at: u64,
+        pub last_used: u64,
+    }
+
+    /// Anchored verifiable credential
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct CredentialRecord {
+        /// Hash of the off-chain credential document
+        pub credential_hash: [u8; 32],
+        /// Issuer account that anchored the credential
+        pub issuer: AccountId,
+        /// Identity the credential is about
+        pub subject: AccountId,
+        /// Credential type (e.g. "KycCredential")
+        pub credential_type: String,
+        pub issued_at: u64,
+        /// Optional timestamp after which the credential is no longer valid
+        pub expires_at: Option<u64>,
+        /// Timestamp of revocation, if revoked
+        pub revoked_at: Option<u64>,
+    }
+
+    /// Result of checking a credential hash
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum CredentialStatus {
+        /// Credential was never anchored
+        Unknown,
+        Valid,
+        Expired,
+        Revoked,
+    }
+
+    /// Key types supported for DID verification methods
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum KeyType {
+        Sr25519,
+        Ed25519,
+        EcdsaSecp256k1,
+        EcdsaP256,
+    }
+
+    /// Verification method of a DID document
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct VerificationMethod {
+        /// Fragment identifying the key within the document (e.g. "key-1")
+        pub id: String,
+        pub key_type: KeyType,
+        /// Account controlling this key
+        pub controller: AccountId,
+        /// Raw public key bytes
+        pub public_key: Vec<u8>,
+    }
+
+    /// Service endpoint of a DID document
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ServiceEndpoint {
+        /// Fragment identifying the service within the document (e.g. "hub")
+        pub id: String,
+        /// Service type (e.g. "LinkedDomains")
+        pub service_type: String,
+        /// Service URI
+        pub endpoint: String,
+    }
+
+    /// Resolved `did:authentify:<account>` document
+    #[derive(Debug, Clone, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct DidDocument {
+        /// The DID itself
+        pub id: String,
+        /// Account allowed to update the document
+        pub controller: AccountId,
+        /// Wallet key (fragment "wallet") followed by added keys
+        pub verification_methods: Vec<VerificationMethod>,
+        pub services: Vec<ServiceEndpoint>,
+        pub created_at: u64,
+    }
+
+    /// Why a login attempt was rejected
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum LoginFailureReason {
+        /// No identity with this username
+        UnknownUser,
+        /// Wrong password or second-factor code
+        BadCredential,
+        /// Account is locked
+        Locked,
+    }
+
+    /// Result of a login attempt
+    ///
+    /// Rejections are returned as `Ok(Failed)` rather than `Err` so the failed
+    /// attempt, its events and any lockout are persisted on-chain.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum LoginOutcome {
+        /// Login completed for the account
+        Success(AccountId),
+        /// Password accepted; complete with `verify_two_factor` before `expires_at`
+        TwoFactorRequired { account: AccountId, expires_at: u64 },
+        /// Login rejected
+        Failed { reason: LoginFailureReason, remaining_attempts: u32 },
+    }
+
+    /// Admin-tunable setting reported by `ConfigUpdated`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum ConfigParameter {
+        MaxFailedAttempts,
+        LockoutDuration,
+        RevealWindow,
+        TwoFactorWindow,
+        /// Values are 0 (disabled) or 1 (enabled)
+        DirectRegistration,
+        RegistrationDeposit,
+        SessionDeposit,
+    }
+
+    /// Operations that can be paused independently by the admin
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PausableOperation {
+        /// Identity registration (direct and commit-reveal)
+        Registration,
+        /// Username/password authentication
+        Authentication,
+        /// Session creation
+        SessionCreation,
+    }
+
+    /// Current pause state of each pausable operation
+    #[derive(Debug, Clone, Default, PartialEq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PauseFlags {
+        pub registration: bool,
+        pub authentication: bool,
+        pub session_creation: bool,
     }
 
     /// Main contract storage
@@ -66,10 +740,100 @@ mod authentify {
         total_users: u64,
         /// Total number of active sessions
         active_sessions: u64,
-        /// Maximum failed login attempts before lockout
-        max_failed_attempts: u32,
-        /// Lockout duration in milliseconds
-        lockout_duration: u64,
+        /// Cumulative verifications granted
+        verifications_granted: u64,
+        /// Cumulative lockouts
+        lockouts: u64,
+        /// Cumulative successful logins
+        total_logins: u64,
+        /// Cumulative failed logins
+        failed_logins: u64,
+        /// Cumulative sessions created
+        sessions_created: u64,
+        /// Cumulative sessions revoked
+        sessions_revoked: u64,
+        /// Admin-tunable settings
+        config: Config,
+        /// Maps AccountId to its pending registration commitment
+        registration_commitments: Mapping<AccountId, RegistrationCommitment, ManualKey<0x434D4954>>, // "CMIT"
+        /// Maps AccountId to the deposit held for its identity
+        identity_deposits: Mapping<AccountId, Balance, ManualKey<0x44455049>>, // "DEPI"
+        /// Maps session_id to the deposit held for the session
+        session_deposits: Mapping<String, SessionDeposit, ManualKey<0x44455053>>, // "DEPS"
+        /// Maps AccountId to the ids of its unrevoked sessions
+        account_sessions: Mapping<AccountId, Vec<String>, ManualKey<0x41534553>>, // "ASES"
+        /// Total deposits currently held by the contract
+        total_deposits: Balance,
+        /// Emergency stop flags for registration, authentication and sessions
+        pause_flags: PauseFlags,
+        /// Maps AccountId to its verification details
+        verifications: Mapping<AccountId, VerificationRecord, ManualKey<0x56524659>>, // "VRFY"
+        /// Accounts allowed to verify identities besides the admin
+        verifiers: Mapping<AccountId, (), ManualKey<0x56455249>>, // "VERI"
+        /// Maps request_id to VerificationRequest
+        verification_requests: Mapping<u64, VerificationRequest, ManualKey<0x56524551>>, // "VREQ"
+        /// Maps AccountId to its pending request_id
+        pending_request_of: Mapping<AccountId, u64, ManualKey<0x56525041>>, // "VRPA"
+        /// IDs of all pending requests (unordered, swap-removed on resolution)
+        pending_requests: StorageVec<u64, ManualKey<0x56525043>>, // "VRPC"
+        /// Maps request_id to its position in `pending_requests`
+        pending_request_index: Mapping<u64, u32, ManualKey<0x56525049>>, // "VRPI"
+        /// ID assigned to the next verification request
+        next_request_id: u64,
+        /// Maps DID subject to its controller when delegated away from the owner
+        did_controllers: Mapping<AccountId, AccountId, ManualKey<0x4449434F>>, // "DICO"
+        /// Maps DID subject to its additional verification methods
+        did_keys: Mapping<AccountId, Vec<VerificationMethod>, ManualKey<0x44494B59>>, // "DIKY"
+        /// Maps DID subject to its service endpoints
+        did_services: Mapping<AccountId, Vec<ServiceEndpoint>, ManualKey<0x44495356>>, // "DISV"
+        /// Accounts allowed to issue credentials
+        issuers: Mapping<AccountId, (), ManualKey<0x49535355>>, // "ISSU"
+        /// Maps credential hash to CredentialRecord
+        credentials: Mapping<[u8; 32], CredentialRecord, ManualKey<0x43524544>>, // "CRED"
+        /// Maps AccountId to its registered passkeys
+        passkeys: Mapping<AccountId, Vec<PasskeyCredential>, ManualKey<0x50534B59>>, // "PSKY"
+        /// Maps WebAuthn credential ID to the owning AccountId
+        passkey_owner: Mapping<Vec<u8>, AccountId, ManualKey<0x504B4F57>>, // "PKOW"
+        /// Maps AccountId to the number of passkey logins it has completed
+        passkey_nonces: Mapping<AccountId, u64, ManualKey<0x504B4E43>>, // "PKNC"
+        /// Maps AccountId to the current anchor of its one-time code chain
+        two_factor_anchors: Mapping<AccountId, [u8; 32], ManualKey<0x54464143>>, // "TFAC"
+        /// Maps AccountId to its login awaiting a second-factor proof
+        pending_logins: Mapping<AccountId, PendingLogin, ManualKey<0x504C4F47>>, // "PLOG"
+        /// Maps AccountId to hashes of its unused recovery codes
+        recovery_codes: Mapping<AccountId, RecoveryCodes, ManualKey<0x52434F44>>, // "RCOD"
+        /// Maps owner AccountId to its delegated session keys
+        session_keys: Mapping<AccountId, Vec<SessionKey>, ManualKey<0x534B4559>>, // "SKEY"
+        /// Maps session ID to the session keys bound to it
+        session_key_links: Mapping<String, Vec<AccountId>, ManualKey<0x534B4C4E>>, // "SKLN"
+        /// Maps client ID to its registered client application
+        clients: Mapping<u64, ClientApp, ManualKey<0x434C4E54>>, // "CLNT"
+        /// Next client ID to assign
+        next_client_id: u64,
+        /// Maps owner AccountId to the number of clients it registered
+        client_counts: Mapping<AccountId, u32, ManualKey<0x434C4354>>, // "CLCT"
+        /// Maps AccountId to the consents it granted to client applications
+        consents: Mapping<AccountId, Vec<ConsentGrant>, ManualKey<0x434E5354>>, // "CNST"
+        /// Maps AccountId to its profile attribute commitments
+        attributes: Mapping<AccountId, Vec<AttributeCommitment>, ManualKey<0x41545452>>, // "ATTR"
+        /// Registered accounts, in registration order except where swap-removed
+        identity_index: StorageVec<AccountId, ManualKey<0x49445856>>, // "IDXV"
+        /// Maps AccountId to its position in `identity_index`
+        identity_index_position: Mapping<AccountId, u32, ManualKey<0x49445850>>, // "IDXP"
+        /// Maps username prefix bucket to its number of entries
+        prefix_bucket_len: Mapping<String, u32, ManualKey<0x50584C4E>>, // "PXLN"
+        /// Maps (prefix bucket, position) to a lowercase username
+        prefix_bucket_entries: Mapping<(String, u32), String, ManualKey<0x5058454E>>, // "PXEN"
+        /// Maps lowercase username to its position in its prefix bucket
+        prefix_bucket_position: Mapping<String, u32, ManualKey<0x50585053>>, // "PXPS"
+        /// Maps social provider to its cumulative registrations
+        provider_registrations: Mapping<String, u64, ManualKey<0x52475056>>, // "RGPV"
+        /// Social providers accepted at registration (admin-managed allowlist)
+        allowed_providers: StorageVec<String, ManualKey<0x50524F56>>, // "PROV"
+        /// Audit ring buffer slots per account
+        audit_entries: Mapping<(AccountId, u32), AuditEntry, ManualKey<0x41554445>>, // "AUDE"
+        /// Total audit entries ever written per account
+        audit_counts: Mapping<AccountId, u64, ManualKey<0x41554443>>, // "AUDC"
     }
 
     /// Events emitted by the contract
@@ -84,28 +848,116 @@ mod authentify {
     }
 
     #[ink(event)]
-    pub struct IdentityVerified {
+    pub struct RegistrationCommitted {
         #[ink(topic)]
         account: AccountId,
-        verified_by: AccountId,
-        timestamp: u64,
+        block_number: BlockNumber,
     }
 
     #[ink(event)]
-    pub struct LoginSuccessful {
+    pub struct IdentityDeleted {
         #[ink(topic)]
         account: AccountId,
-        #[ink(topic)]
         username: String,
+        refunded: Balance,
         timestamp: u64,
     }
 
     #[ink(event)]
-    pub struct LoginFailed {
+    pub struct IdentityVerified {
         #[ink(topic)]
-        username: String,
-        reason: String,
-        timestamp: u64,
+        account: AccountId,
+        level: VerificationLevel,
+        verified_by: AccountId,
+        expires_at: Option<u64>,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct VerificationRequested {
+        #[ink(topic)]
+        account: AccountId,
+        request_id: u64,
+        level: VerificationLevel,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct VerificationRequestResolved {
+        #[ink(topic)]
+        account: AccountId,
+        request_id: u64,
+        status: RequestStatus,
+        resolved_by: AccountId,
+        reason: Option<String>,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct VerifierUpdated {
+        #[ink(topic)]
+        verifier: AccountId,
+        enabled: bool,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct IssuerUpdated {
+        #[ink(topic)]
+        issuer: AccountId,
+        enabled: bool,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct CredentialIssued {
+        #[ink(topic)]
+        subject: AccountId,
+        #[ink(topic)]
+        issuer: AccountId,
+        credential_hash: [u8; 32],
+        credential_type: String,
+        expires_at: Option<u64>,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct CredentialRevoked {
+        #[ink(topic)]
+        subject: AccountId,
+        #[ink(topic)]
+        issuer: AccountId,
+        credential_hash: [u8; 32],
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct VerificationRevoked {
+        #[ink(topic)]
+        account: AccountId,
+        revoked_by: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct LoginSuccessful {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        username: String,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct LoginFailed {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        username: String,
+        reason: LoginFailureReason,
+        /// Attempts left before the account locks
+        remaining_attempts: u32,
+        timestamp: u64,
     }
 
     #[ink(event)]
@@ -131,12 +983,228 @@ mod authentify {
         account: AccountId,
         session_id: String,
         expires_at: u64,
+        client_id: Option<u64>,
     }
 
     #[ink(event)]
     pub struct SessionRevoked {
         #[ink(topic)]
         session_id: String,
+        refunded: Balance,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct ClientRegistered {
+        #[ink(topic)]
+        client_id: u64,
+        #[ink(topic)]
+        owner: AccountId,
+        name: String,
+    }
+
+    #[ink(event)]
+    pub struct ClientUpdated {
+        #[ink(topic)]
+        client_id: u64,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct ClientDisabled {
+        #[ink(topic)]
+        client_id: u64,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct AttributeSet {
+        #[ink(topic)]
+        account: AccountId,
+        key: String,
+        commitment: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct AttributeRemoved {
+        #[ink(topic)]
+        account: AccountId,
+        key: String,
+    }
+
+    #[ink(event)]
+    pub struct ConsentGranted {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        client_id: u64,
+        scopes: Vec<String>,
+        expires_at: u64,
+    }
+
+    #[ink(event)]
+    pub struct ConsentRevoked {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        client_id: u64,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct SessionKeyAdded {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        key: AccountId,
+        scopes: Vec<String>,
+        expires_at: u64,
+        session_id: Option<String>,
+    }
+
+    #[ink(event)]
+    pub struct SessionKeyRevoked {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        key: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct DidKeyAdded {
+        #[ink(topic)]
+        subject: AccountId,
+        key_id: String,
+        key_type: KeyType,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct DidKeyRemoved {
+        #[ink(topic)]
+        subject: AccountId,
+        key_id: String,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct DidServiceAdded {
+        #[ink(topic)]
+        subject: AccountId,
+        service_id: String,
+        endpoint: String,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct DidServiceRemoved {
+        #[ink(topic)]
+        subject: AccountId,
+        service_id: String,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct DidControllerChanged {
+        #[ink(topic)]
+        subject: AccountId,
+        #[ink(topic)]
+        new_controller: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct PauseStatusChanged {
+        #[ink(topic)]
+        operation: PausableOperation,
+        paused: bool,
+        changed_by: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct AdminTransferred {
+        #[ink(topic)]
+        previous_admin: AccountId,
+        #[ink(topic)]
+        new_admin: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ConfigInitialized {
+        config: Config,
+        initialized_by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ConfigUpdated {
+        #[ink(topic)]
+        parameter: ConfigParameter,
+        old_value: u128,
+        new_value: u128,
+        updated_by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RegistrationCommitmentCleared {
+        #[ink(topic)]
+        account: AccountId,
+        cleared_by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TwoFactorEnabled {
+        #[ink(topic)]
+        account: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct TwoFactorDisabled {
+        #[ink(topic)]
+        account: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct TwoFactorChallenge {
+        #[ink(topic)]
+        account: AccountId,
+        expires_at: u64,
+    }
+
+    #[ink(event)]
+    pub struct RecoveryCodesSet {
+        #[ink(topic)]
+        account: AccountId,
+        count: u32,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct RecoveryCodeConsumed {
+        #[ink(topic)]
+        account: AccountId,
+        action: RecoveryAction,
+        remaining: u32,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct PasskeyAdded {
+        #[ink(topic)]
+        account: AccountId,
+        credential_id: Vec<u8>,
+        label: String,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct PasskeyRemoved {
+        #[ink(topic)]
+        account: AccountId,
+        credential_id: Vec<u8>,
         timestamp: u64,
     }
 
@@ -147,6 +1215,13 @@ mod authentify {
         timestamp: u64,
     }
 
+    #[ink(event)]
+    pub struct ProviderUpdated {
+        provider: String,
+        allowed: bool,
+        updated_by: AccountId,
+    }
+
     /// Error types
     #[derive(Debug, PartialEq, Eq, parity_scale_codec::Encode, parity_scale_codec::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -183,10 +1258,165 @@ mod authentify {
         SessionExpired,
         /// Session already revoked
         SessionAlreadyRevoked,
+        /// Single-call registration is disabled, use commit-reveal
+        DirectRegistrationDisabled,
+        /// No registration commitment found for this account
+        CommitmentNotFound,
+        /// Reveal must happen at least one block after the commitment
+        RevealTooEarly,
+        /// Registration commitment has expired
+        CommitmentExpired,
+        /// Revealed data does not match the commitment
+        CommitmentMismatch,
+        /// Registration commitment is still within its reveal window
+        CommitmentStillActive,
+        /// Transferred value is below the required deposit
+        InsufficientDeposit,
+        /// Refunding a deposit failed
+        TransferFailed,
+        /// Session with this ID already exists
+        SessionAlreadyExists,
+        /// Operation is paused by the admin
+        Paused,
+        /// Identity has no verification
+        NotVerified,
+        /// Expiry timestamp is not in the future
+        InvalidExpiry,
+        /// Account already has a pending verification request
+        RequestAlreadyPending,
+        /// Verification request not found
+        RequestNotFound,
+        /// Verification request has already been approved or rejected
+        RequestNotPending,
+        /// DID fragment is empty, too long or reserved
+        InvalidDidFragment,
+        /// Public key or service endpoint is empty or too long
+        InvalidDidEntry,
+        /// A key or service with this fragment already exists
+        DidEntryAlreadyExists,
+        /// No key or service with this fragment exists
+        DidEntryNotFound,
+        /// Maximum number of keys or services reached
+        TooManyDidEntries,
+        /// Credential type cannot be empty
+        EmptyCredentialType,
+        /// Credential hash already anchored
+        CredentialAlreadyExists,
+        /// Credential not found
+        CredentialNotFound,
+        /// Credential already revoked
+        CredentialAlreadyRevoked,
+        /// WebAuthn credential ID is empty or too long
+        InvalidCredentialId,
+        /// Passkey public key is not a valid COSE ES256 key
+        InvalidPasskeyKey,
+        /// Passkey label is too long (maximum 64 characters)
+        PasskeyLabelTooLong,
+        /// WebAuthn credential ID is already registered
+        PasskeyAlreadyRegistered,
+        /// Passkey not found
+        PasskeyNotFound,
+        /// Maximum number of passkeys reached
+        TooManyPasskeys,
+        /// Authenticator data is malformed, lacks user presence or has the wrong RP ID
+        InvalidAuthenticatorData,
+        /// Assertion signature does not verify
+        InvalidSignature,
+        /// Signature counter did not increase (possible cloned authenticator)
+        SignCountRegression,
+        /// Two-factor authentication is already enabled
+        TwoFactorAlreadyEnabled,
+        /// Two-factor authentication is not enabled
+        TwoFactorNotEnabled,
+        /// No login is waiting for a second-factor proof
+        NoPendingLogin,
+        /// The pending login timed out
+        TwoFactorExpired,
+        /// Second-factor proof does not match the enrollment
+        InvalidTwoFactorCode,
+        /// More recovery codes than `MAX_RECOVERY_CODES`
+        TooManyRecoveryCodes,
+        /// Recovery code is unknown or already used
+        InvalidRecoveryCode,
+        /// Account is not locked
+        AccountNotLocked,
+        /// Scope list is empty, too long or contains an invalid scope
+        InvalidScope,
+        /// Session key is already registered for this owner
+        SessionKeyAlreadyExists,
+        /// Session key not found
+        SessionKeyNotFound,
+        /// More session keys than `MAX_SESSION_KEYS`
+        TooManySessionKeys,
+        /// Client name is empty or too long
+        InvalidClientName,
+        /// Redirect URI list is empty, too long or contains an invalid URI
+        InvalidRedirectUri,
+        /// Client application not found
+        ClientNotFound,
+        /// Client application is disabled
+        ClientDisabled,
+        /// Session was issued to a different client
+        AudienceMismatch,
+        /// Account has no valid consent for the client application
+        ConsentRequired,
+        /// Consent not found
+        ConsentNotFound,
+        /// More consents than `MAX_CONSENTS`
+        TooManyConsents,
+        /// Attribute key is empty or too long
+        InvalidAttributeKey,
+        /// More attributes than `MAX_ATTRIBUTES`
+        TooManyAttributes,
+        /// Attribute not found
+        AttributeNotFound,
+        /// Search prefix is shorter than `USERNAME_PREFIX_LEN` characters
+        InvalidPrefix,
+        /// More items than `MAX_BATCH_SIZE`
+        BatchTooLarge,
+        /// Config `max_failed_attempts` outside `MAX_FAILED_ATTEMPTS_RANGE`
+        InvalidMaxFailedAttempts,
+        /// Config `lockout_duration` outside `LOCKOUT_DURATION_RANGE`
+        InvalidLockoutDuration,
+        /// More unrevoked sessions than `MAX_SESSIONS_PER_ACCOUNT`
+        TooManySessions,
+        /// Credential type is longer than `MAX_CREDENTIAL_TYPE_LEN`
+        CredentialTypeTooLong,
+        /// clientDataJSON is not a `webauthn.get` assertion
+        InvalidClientData,
+        /// clientDataJSON does not carry the challenge from `get_passkey_challenge`
+        ChallengeMismatch,
+        /// Owner already registered `MAX_CLIENTS_PER_OWNER` client applications
+        TooManyClients,
+        /// Social provider is not on the allowlist
+        ProviderNotAllowed,
+        /// Social provider name is empty or too long
+        InvalidProvider,
+        /// Allowlist already holds `MAX_PROVIDERS` providers
+        TooManyProviders,
+        /// Config `reveal_window` outside `REVEAL_WINDOW_RANGE`
+        InvalidRevealWindow,
+        /// Config `two_factor_window` outside `TWO_FACTOR_WINDOW_RANGE`
+        InvalidTwoFactorWindow,
+        /// Config deposit above `MAX_DEPOSIT`
+        InvalidDeposit,
     }
 
     pub type Result<T> = core::result::Result<T, Error>;
 
+    impl From<WebAuthnError> for Error {
+        fn from(error: WebAuthnError) -> Self {
+            match error {
+                WebAuthnError::InvalidPublicKey => Error::InvalidPasskeyKey,
+                WebAuthnError::InvalidAuthenticatorData
+                | WebAuthnError::UserNotPresent => Error::InvalidAuthenticatorData,
+                WebAuthnError::InvalidSignature => Error::InvalidSignature,
+                WebAuthnError::InvalidClientData => Error::InvalidClientData,
+                WebAuthnError::ChallengeMismatch => Error::ChallengeMismatch,
+            }
+        }
+    }
+
     impl Default for Authentify {
         fn default() -> Self {
             Self::new()
@@ -197,26 +1427,22 @@ mod authentify {
         /// Constructor - initializes the contract with default settings
         #[ink(constructor)]
         pub fn new() -> Self {
-            Self {
-                identities: Mapping::default(),
-                username_to_account: Mapping::default(),
-                social_to_account: Mapping::default(),
-                sessions: Mapping::default(),
-                admin: Self::env().caller(),
-                total_users: 0,
-                active_sessions: 0,
-                max_failed_attempts: 5,
-                lockout_duration: 900000, // 15 minutes in milliseconds
-            }
+            Self::from_config(Config::default())
         }
 
         /// Alternative constructor with custom settings
+        ///
+        /// Fails with the matching `Invalid*` error, and the instantiation reverts,
+        /// when a setting is outside its accepted range.
         #[ink(constructor)]
-        pub fn new_with_config(
-            max_failed_attempts: u32,
-            lockout_duration: u64,
-        ) -> Self {
-            Self {
+        pub fn new_with_config(config: Config) -> Result<Self> {
+            config.validate()?;
+            Ok(Self::from_config(config))
+        }
+
+        /// Shared constructor body; `config` must already be valid
+        fn from_config(config: Config) -> Self {
+            let mut contract = Self {
                 identities: Mapping::default(),
                 username_to_account: Mapping::default(),
                 social_to_account: Mapping::default(),
@@ -224,9 +1450,61 @@ mod authentify {
                 admin: Self::env().caller(),
                 total_users: 0,
                 active_sessions: 0,
-                max_failed_attempts,
-                lockout_duration,
-            }
+                verifications_granted: 0,
+                lockouts: 0,
+                total_logins: 0,
+                failed_logins: 0,
+                sessions_created: 0,
+                sessions_revoked: 0,
+                config,
+                registration_commitments: Mapping::default(),
+                identity_deposits: Mapping::default(),
+                session_deposits: Mapping::default(),
+                account_sessions: Mapping::default(),
+                total_deposits: 0,
+                pause_flags: PauseFlags::default(),
+                verifications: Mapping::default(),
+                verifiers: Mapping::default(),
+                verification_requests: Mapping::default(),
+                pending_request_of: Mapping::default(),
+                pending_requests: StorageVec::new(),
+                pending_request_index: Mapping::default(),
+                next_request_id: 0,
+                did_controllers: Mapping::default(),
+                did_keys: Mapping::default(),
+                did_services: Mapping::default(),
+                issuers: Mapping::default(),
+                credentials: Mapping::default(),
+                passkeys: Mapping::default(),
+                passkey_owner: Mapping::default(),
+                passkey_nonces: Mapping::default(),
+                two_factor_anchors: Mapping::default(),
+                pending_logins: Mapping::default(),
+                recovery_codes: Mapping::default(),
+                session_keys: Mapping::default(),
+                session_key_links: Mapping::default(),
+                clients: Mapping::default(),
+                next_client_id: 0,
+                client_counts: Mapping::default(),
+                consents: Mapping::default(),
+                attributes: Mapping::default(),
+                identity_index: StorageVec::new(),
+                identity_index_position: Mapping::default(),
+                prefix_bucket_len: Mapping::default(),
+                prefix_bucket_entries: Mapping::default(),
+                prefix_bucket_position: Mapping::default(),
+                provider_registrations: Mapping::default(),
+                allowed_providers: StorageVec::new(),
+                audit_entries: Mapping::default(),
+                audit_counts: Mapping::default(),
+            };
+
+            contract.allow_default_providers();
+            Self::env().emit_event(ConfigInitialized {
+                config: contract.config,
+                initialized_by: contract.admin,
+            });
+            contract
         }
 
         // ========================================
@@ -240,11 +1518,14 @@ mod authentify {
         /// * `password_hash` - Bcrypt hashed password (hashed client-side)
         /// * `social_id_hash` - Hash of social identifier (e.g., hash of "google:user@gmail.com")
         /// * `social_provider` - Social provider name (google, github, twitter, etc.)
+        ///
+        /// The transferred value must cover `registration_deposit` and is refunded
+        /// on `delete_identity`.
         /// 
         /// # Returns
         /// * `Ok(())` if registration successful
         /// * `Err(Error)` if validation fails
-        #[ink(message)]
+        #[ink(message, payable)]
         pub fn register_identity(
             &mut self,
             username: String,
@@ -252,44 +1533,163 @@ mod authentify {
             social_id_hash: String,
             social_provider: String,
         ) -> Result<()> {
+            self.ensure_not_paused(PausableOperation::Registration)?;
+
+            if !self.config.direct_registration_enabled {
+                return Err(Error::DirectRegistrationDisabled);
+            }
+
             let caller = self.env().caller();
-            let timestamp = self.env().block_timestamp();
+            self.do_register_identity(caller, username, password_hash, social_id_hash, social_provider)
+        }
 
-            // Validate inputs
-            self.validate_username(&username)?;
-            self.validate_password_hash(&password_hash)?;
-            self.validate_social_id_hash(&social_id_hash)?;
+        /// Commit to a registration without revealing the username
+        ///
+        /// # Arguments
+        /// * `commitment` - Blake2x256 hash of SCALE-encoded `(username, salt, account)`,
+        ///   see `compute_registration_commitment`
+        ///
+        /// The matching `reveal_registration` must be sent at least one block later
+        /// and within `reveal_window` blocks. A new commitment replaces any pending one.
+        #[ink(message)]
+        pub fn commit_registration(&mut self, commitment: [u8; 32]) -> Result<()> {
+            self.ensure_not_paused(PausableOperation::Registration)?;
 
-            // Convert username to lowercase for case-insensitive lookup
-            let username_lower = username.to_lowercase();
+            let caller = self.env().caller();
+            let block_number = self.env().block_number();
 
-            // Check if identity already exists
             if self.identities.contains(caller) {
                 return Err(Error::IdentityAlreadyExists);
             }
 
-            // Check if username is already taken
-            if self.username_to_account.contains(&username_lower) {
-                return Err(Error::UsernameAlreadyTaken);
-            }
+            self.registration_commitments.insert(caller, &RegistrationCommitment {
+                commitment,
+                committed_at: block_number,
+            });
 
-            // Check if social ID is already bound
-            if self.social_to_account.contains(&social_id_hash) {
-                return Err(Error::SocialIdAlreadyBound);
-            }
+            self.env().emit_event(RegistrationCommitted {
+                account: caller,
+                block_number,
+            });
 
-            // Create identity
-            let identity = IdentityInfo {
-                username: username.clone(),
-                password_hash,
-                social_id_hash: social_id_hash.clone(),
+            Ok(())
+        }
+
+        /// Reveal a previously committed registration and register the identity
+        ///
+        /// # Arguments
+        /// * `username`, `password_hash`, `social_id_hash`, `social_provider` - As for `register_identity`
+        /// * `salt` - Salt used when computing the commitment
+        ///
+        /// The transferred value must cover `registration_deposit`.
+        #[ink(message, payable)]
+        pub fn reveal_registration(
+            &mut self,
+            username: String,
+            password_hash: String,
+            social_id_hash: String,
+            social_provider: String,
+            salt: [u8; 32],
+        ) -> Result<()> {
+            self.ensure_not_paused(PausableOperation::Registration)?;
+
+            let caller = self.env().caller();
+            let block_number = self.env().block_number();
+
+            let pending = self.registration_commitments.get(caller)
+                .ok_or(Error::CommitmentNotFound)?;
+
+            if block_number <= pending.committed_at {
+                return Err(Error::RevealTooEarly);
+            }
+
+            if block_number > pending.committed_at.saturating_add(self.config.reveal_window) {
+                return Err(Error::CommitmentExpired);
+            }
+
+            if compute_registration_commitment(&username, salt, caller) != pending.commitment {
+                return Err(Error::CommitmentMismatch);
+            }
+
+            self.do_register_identity(caller, username, password_hash, social_id_hash, social_provider)?;
+            self.registration_commitments.remove(caller);
+
+            Ok(())
+        }
+
+        /// Remove an expired registration commitment (callable by anyone)
+        #[ink(message)]
+        pub fn clear_expired_commitment(&mut self, account: AccountId) -> Result<()> {
+            let pending = self.registration_commitments.get(account)
+                .ok_or(Error::CommitmentNotFound)?;
+
+            if self.env().block_number() <= pending.committed_at.saturating_add(self.config.reveal_window) {
+                return Err(Error::CommitmentStillActive);
+            }
+
+            self.registration_commitments.remove(account);
+
+            self.env().emit_event(RegistrationCommitmentCleared {
+                account,
+                cleared_by: self.env().caller(),
+            });
+
+            Ok(())
+        }
+
+        fn do_register_identity(
+            &mut self,
+            caller: AccountId,
+            username: String,
+            password_hash: String,
+            social_id_hash: String,
+            social_provider: String,
+        ) -> Result<()> {
+            let timestamp = self.env().block_timestamp();
+            let deposit = self.env().transferred_value();
+
+            if deposit < self.config.registration_deposit {
+                return Err(Error::InsufficientDeposit);
+            }
+
+            // Validate inputs
+            self.validate_username(&username)?;
+            self.validate_password_hash(&password_hash)?;
+            self.validate_social_id_hash(&social_id_hash)?;
+            if !self.is_provider_allowed(social_provider.clone()) {
+                return Err(Error::ProviderNotAllowed);
+            }
+
+            // Convert username to lowercase for case-insensitive lookup
+            let username_lower = username.to_lowercase();
+
+            // Check if identity already exists
+            if self.identities.contains(caller) {
+                return Err(Error::IdentityAlreadyExists);
+            }
+
+            // Check if username is already taken
+            if self.username_to_account.contains(&username_lower) {
+                return Err(Error::UsernameAlreadyTaken);
+            }
+
+            // Check if social ID is already bound
+            if self.social_to_account.contains(&social_id_hash) {
+                return Err(Error::SocialIdAlreadyBound);
+            }
+
+            // Create identity
+            let identity = IdentityInfo {
+                username: username.clone(),
+                password_hash,
+                social_id_hash: social_id_hash.clone(),
                 social_provider: social_provider.clone(),
                 wallet_address: caller,
-                is_verified: false, // Requires admin verification
                 created_at: timestamp,
                 last_login: 0,
                 failed_attempts: 0,
                 is_locked: false,
+                two_factor_enabled: false,
             };
 
             // Store identity mappings
@@ -297,6 +1697,15 @@ mod authentify {
             self.username_to_account.insert(&username_lower, &caller);
             self.social_to_account.insert(&social_id_hash, &caller);
             self.total_users = self.total_users.saturating_add(1);
+            self.identity_index_position.insert(caller, &self.identity_index.len());
+            self.identity_index.push(&caller);
+            self.record_provider_registration(&identity.social_provider);
+            self.add_to_prefix_bucket(&username_lower);
+
+            if deposit > 0 {
+                self.identity_deposits.insert(caller, &deposit);
+                self.total_deposits = self.total_deposits.saturating_add(deposit);
+            }
 
             // Emit event
             self.env().emit_event(IdentityRegistered {
@@ -321,66 +1730,48 @@ mod authentify {
         /// * `password_hash` - Bcrypt hashed password to verify
         /// 
         /// # Returns
-        /// * `Ok(AccountId)` if authentication successful
-        /// * `Err(Error)` if authentication fails
+        /// * `Ok(LoginOutcome::Success)` if authentication successful
+        /// * `Ok(LoginOutcome::TwoFactorRequired)` if the password is accepted but
+        ///   the account has 2FA enabled
+        /// * `Ok(LoginOutcome::Failed)` if authentication fails; returning `Ok` keeps
+        ///   the failed-attempt count, the lockout and the `LoginFailed` event on-chain
+        /// * `Err(Error::Paused)` while authentication is paused; nothing is recorded
         #[ink(message)]
         pub fn authenticate(
             &mut self,
             username: String,
             password_hash: String,
-        ) -> Result<AccountId> {
+        ) -> Result<LoginOutcome> {
+            self.ensure_not_paused(PausableOperation::Authentication)?;
+
             let timestamp = self.env().block_timestamp();
             let username_lower = username.to_lowercase();
 
             // Get account from username
-            let account = self.username_to_account.get(&username_lower)
-                .ok_or(Error::IdentityNotFound)?;
+            let Some(account) = self.username_to_account.get(&username_lower) else {
+                return Ok(self.login_failed(None, username, LoginFailureReason::UnknownUser, timestamp));
+            };
 
             // Get identity
             let mut identity = self.identities.get(account)
                 .ok_or(Error::IdentityNotFound)?;
 
             // Check if account is locked
-            if identity.is_locked {
-                // Check if lockout period has passed
-                if timestamp < identity.last_login.saturating_add(self.lockout_duration) {
-                    self.env().emit_event(LoginFailed {
-                        username: username.clone(),
-                        reason: String::from("Account locked"),
-                        timestamp,
-                    });
-                    return Err(Error::AccountLocked);
-                } else {
-                    // Unlock account if lockout period passed
-                    identity.is_locked = false;
-                    identity.failed_attempts = 0;
-                }
+            if self.is_lock_active(&identity, timestamp) {
+                return Ok(self.login_failed(Some(account), username, LoginFailureReason::Locked, timestamp));
             }
+            self.clear_lapsed_lock(&mut identity, timestamp);
 
             // Verify password hash
             if identity.password_hash != password_hash {
-                // Increment failed attempts
-                identity.failed_attempts = identity.failed_attempts.saturating_add(1);
-                identity.last_login = timestamp;
-
-                // Lock account if max attempts reached
-                if identity.failed_attempts >= self.max_failed_attempts {
-                    identity.is_locked = true;
-                    self.env().emit_event(AccountLocked {
-                        account,
-                        username: username.clone(),
-                        reason: String::from("Too many failed login attempts"),
-                        timestamp,
-                    });
-                }
+                self.record_failed_attempt(account, &mut identity, timestamp);
+                return Ok(self.login_failed(Some(account), username, LoginFailureReason::BadCredential, timestamp));
+            }
 
+            // Password accepted - hold the login until the second factor arrives
+            if identity.two_factor_enabled {
                 self.identities.insert(account, &identity);
-                self.env().emit_event(LoginFailed {
-                    username,
-                    reason: String::from("Invalid password"),
-                    timestamp,
-                });
-                return Err(Error::InvalidCredentials);
+                return Ok(self.start_two_factor(account, timestamp));
             }
 
             // Successful login - reset failed attempts
@@ -388,6 +1779,9 @@ mod authentify {
             identity.last_login = timestamp;
             self.identities.insert(account, &identity);
 
+            self.total_logins = self.total_logins.saturating_add(1);
+            self.record_audit(account, AuditAction::LoginSucceeded);
+
             // Emit success event
             self.env().emit_event(LoginSuccessful {
                 account,
@@ -395,7 +1789,158 @@ mod authentify {
                 timestamp,
             });
 
-            Ok(account)
+            Ok(LoginOutcome::Success(account))
+        }
+
+        /// Complete a login that requires a second factor
+        ///
+        /// # Arguments
+        /// * `username` - User's username
+        /// * `proof` - Next one-time code: the preimage of the enrolled anchor,
+        ///   up to `TWO_FACTOR_LOOKAHEAD` links further down the chain
+        ///
+        /// # Returns
+        /// * `Ok(LoginOutcome::Success)` if the proof is valid within the window
+        /// * `Ok(LoginOutcome::Failed)` for a bad proof, which counts as a failed
+        ///   attempt, or a locked account
+        /// * `Err(Error)` if there is no pending login or it has expired
+        #[ink(message)]
+        pub fn verify_two_factor(&mut self, username: String, proof: [u8; 32]) -> Result<LoginOutcome> {
+            self.ensure_not_paused(PausableOperation::Authentication)?;
+
+            let timestamp = self.env().block_timestamp();
+
+            let account = self.username_to_account.get(username.to_lowercase())
+                .ok_or(Error::IdentityNotFound)?;
+            let mut identity = self.identities.get(account)
+                .ok_or(Error::IdentityNotFound)?;
+
+            let pending = self.pending_logins.get(account)
+                .ok_or(Error::NoPendingLogin)?;
+
+            // The stale entry is replaced by the next password step
+            if is_expired(timestamp, pending.expires_at) {
+                return Err(Error::TwoFactorExpired);
+            }
+
+            if self.is_lock_active(&identity, timestamp) {
+                return Ok(self.login_failed(Some(account), username, LoginFailureReason::Locked, timestamp));
+            }
+            // A lock that lapsed while the login was pending starts a fresh count
+            self.clear_lapsed_lock(&mut identity, timestamp);
+
+            if !self.check_two_factor_proof(account, proof) {
+                self.record_failed_attempt(account, &mut identity, timestamp);
+                if identity.is_locked {
+                    self.pending_logins.remove(account);
+                }
+                return Ok(self.login_failed(Some(account), username, LoginFailureReason::BadCredential, timestamp));
+            }
+
+            self.pending_logins.remove(account);
+            identity.failed_attempts = 0;
+            identity.last_login = timestamp;
+            self.identities.insert(account, &identity);
+            self.total_logins = self.total_logins.saturating_add(1);
+            self.record_audit(account, AuditAction::LoginSucceeded);
+
+            self.env().emit_event(LoginSuccessful {
+                account,
+                username: identity.username,
+                timestamp,
+            });
+
+            Ok(LoginOutcome::Success(account))
+        }
+
+        /// Authenticate with a WebAuthn/passkey assertion
+        ///
+        /// # Arguments
+        /// * `credential_id` - WebAuthn credential ID returned by the authenticator
+        /// * `authenticator_data` - Raw authenticator data of the assertion
+        /// * `client_data_json` - Raw clientDataJSON of a `webauthn.get` ceremony whose
+        ///   challenge is `get_passkey_challenge` of the owning account
+        /// * `signature` - DER-encoded ES256 signature
+        ///
+        /// Each successful assertion consumes the challenge, so it cannot be replayed
+        /// even by authenticators that keep their signature counter at 0. A passkey
+        /// replaces the password step only: with 2FA enabled the login is held until
+        /// `verify_two_factor`, as after `authenticate`.
+        ///
+        /// # Returns
+        /// * `Ok(LoginOutcome::Success)` if the assertion verifies
+        /// * `Ok(LoginOutcome::TwoFactorRequired)` if the account has 2FA enabled
+        /// * `Err(Error)` if authentication fails; the call reverts, so a rejected
+        ///   assertion emits no `LoginFailed` and is not counted toward the lockout
+        #[ink(message)]
+        pub fn authenticate_with_passkey(
+            &mut self,
+            credential_id: Vec<u8>,
+            authenticator_data: Vec<u8>,
+            client_data_json: Vec<u8>,
+            signature: Vec<u8>,
+        ) -> Result<LoginOutcome> {
+            self.ensure_not_paused(PausableOperation::Authentication)?;
+
+            let timestamp = self.env().block_timestamp();
+
+            let account = self.passkey_owner.get(&credential_id)
+                .ok_or(Error::PasskeyNotFound)?;
+            let mut identity = self.identities.get(account)
+                .ok_or(Error::IdentityNotFound)?;
+
+            if self.is_lock_active(&identity, timestamp) {
+                return Err(Error::AccountLocked);
+            }
+            self.clear_lapsed_lock(&mut identity, timestamp);
+
+            let mut passkeys = self.passkeys.get(account).unwrap_or_default();
+            let passkey = passkeys.iter_mut()
+                .find(|passkey| passkey.credential_id == credential_id)
+                .ok_or(Error::PasskeyNotFound)?;
+
+            let auth_data = webauthn::parse_authenticator_data(&authenticator_data)?;
+            if auth_data.rp_id_hash != passkey.rp_id_hash {
+                return Err(Error::InvalidAuthenticatorData);
+            }
+
+            let nonce = self.passkey_nonces.get(account).unwrap_or(0);
+            webauthn::check_client_data(&client_data_json, &self.passkey_challenge(account, nonce))?;
+
+            let key = webauthn::parse_cose_p256_key(&passkey.public_key)?;
+            let client_data_hash = self.env().hash_bytes::<Sha2x256>(&client_data_json);
+            webauthn::verify_assertion(&key, &authenticator_data, &client_data_hash, &signature)?;
+
+            // Authenticators that support counters must strictly increase them
+            if (auth_data.sign_count != 0 || passkey.sign_count != 0)
+                && auth_data.sign_count <= passkey.sign_count
+            {
+                return Err(Error::SignCountRegression);
+            }
+
+            passkey.sign_count = auth_data.sign_count;
+            passkey.last_used = timestamp;
+            self.passkeys.insert(account, &passkeys);
+            self.passkey_nonces.insert(account, &nonce.saturating_add(1));
+
+            if identity.two_factor_enabled {
+                self.identities.insert(account, &identity);
+                return Ok(self.start_two_factor(account, timestamp));
+            }
+
+            identity.failed_attempts = 0;
+            identity.last_login = timestamp;
+            self.identities.insert(account, &identity);
+            self.total_logins = self.total_logins.saturating_add(1);
+            self.record_audit(account, AuditAction::LoginSucceeded);
+
+            self.env().emit_event(LoginSuccessful {
+                account,
+                username: identity.username,
+                timestamp,
+            });
+
+            Ok(LoginOutcome::Success(account))
         }
 
         /// Verify password for an account (used by backend for additional checks)
@@ -408,7 +1953,7 @@ mod authentify {
             let identity = self.identities.get(account)
                 .ok_or(Error::IdentityNotFound)?;
 
-            if identity.is_locked {
+            if self.is_lock_active(&identity, self.env().block_timestamp()) {
                 return Err(Error::AccountLocked);
             }
 
@@ -420,15 +1965,68 @@ mod authentify {
         // ========================================
 
         /// Create a new session after successful authentication
-        #[ink(message)]
+        ///
+        /// Only for accounts with a registered identity. The transferred value must
+        /// cover `session_deposit` and is refunded to the caller on `revoke_session`.
+        /// Without a `client_id` the caller must be `account` itself or the admin.
+        /// With a `client_id` the session is scoped to that client application, which
+        /// must be enabled and hold a valid consent of `account`; only `account` or
+        /// the client's owner may create it, so nobody else can mint a session the
+        /// client would accept.
+        #[ink(message, payable)]
         pub fn create_session(
             &mut self,
             account: AccountId,
             session_id: String,
             duration_ms: u64,
+            client_id: Option<u64>,
         ) -> Result<()> {
+            self.ensure_not_paused(PausableOperation::SessionCreation)?;
+
+            if !self.identities.contains(account) {
+                return Err(Error::IdentityNotFound);
+            }
+
+            let caller = self.env().caller();
+            let authorized = match client_id {
+                Some(client_id) => {
+                    caller == account
+                        || self.clients.get(client_id).is_some_and(|client| client.owner == caller)
+                }
+                None => self.acts_for(account),
+            };
+            if !authorized {
+                return Err(Error::Unauthorized);
+            }
+
+            if let Some(client_id) = client_id {
+                self.ensure_client_enabled(client_id)?;
+                self.ensure_consent(account, client_id)?;
+            }
+
             let timestamp = self.env().block_timestamp();
             let expires_at = timestamp.saturating_add(duration_ms);
+            let deposit = self.env().transferred_value();
+
+            if deposit < self.config.session_deposit {
+                return Err(Error::InsufficientDeposit);
+            }
+
+            if self.sessions.contains(&session_id) {
+                return Err(Error::SessionAlreadyExists);
+            }
+
+            // Expired and closed sessions no longer count toward the cap
+            let mut account_sessions = self.account_sessions.get(account).unwrap_or_default();
+            account_sessions.retain(|id| {
+                self.sessions.get(id)
+                    .is_some_and(|session| session.is_active && !is_expired(timestamp, session.expires_at))
+            });
+            if account_sessions.len() >= MAX_SESSIONS_PER_ACCOUNT {
+                return Err(Error::TooManySessions);
+            }
+            account_sessions.push(session_id.clone());
+            self.account_sessions.insert(account, &account_sessions);
 
             let session = SessionInfo {
                 account,
@@ -436,562 +2034,4975 @@ mod authentify {
                 created_at: timestamp,
                 expires_at,
                 is_active: true,
+                client_id,
             };
 
             self.sessions.insert(&session_id, &session);
             self.active_sessions = self.active_sessions.saturating_add(1);
+            self.sessions_created = self.sessions_created.saturating_add(1);
+
+            if deposit > 0 {
+                self.session_deposits.insert(&session_id, &SessionDeposit {
+                    payer: self.env().caller(),
+                    amount: deposit,
+                });
+                self.total_deposits = self.total_deposits.saturating_add(deposit);
+            }
 
             self.env().emit_event(SessionCreated {
                 account,
                 session_id,
                 expires_at,
+                client_id,
             });
+            self.record_audit(account, AuditAction::SessionCreated);
 
             Ok(())
         }
 
-        /// Verify if a session is valid
+        /// Verify if a session is valid for the given audience
+        ///
+        /// # Arguments
+        /// * `session_id` - Session to check
+        /// * `client_id` - Expected audience; `None` for sessions not scoped to a client
         #[ink(message)]
-        pub fn verify_session(&self, session_id: String) -> Result<AccountId> {
-            let session = self.sessions.get(&session_id)
-                .ok_or(Error::SessionNotFound)?;
-
-            if !session.is_active {
-                return Err(Error::SessionAlreadyRevoked);
-            }
+        pub fn verify_session(&self, session_id: String, client_id: Option<u64>) -> Result<AccountId> {
+            let session = self.check_session(&session_id)?;
 
-            let timestamp = self.env().block_timestamp();
-            if timestamp > session.expires_at {
-                return Err(Error::SessionExpired);
+            if session.client_id != client_id {
+                return Err(Error::AudienceMismatch);
             }
 
             Ok(session.account)
         }
 
         /// Revoke a session (logout)
+        ///
+        /// Callable by the session's account, the payer of its deposit or the admin;
+        /// session keys bound to the session are revoked with it.
         #[ink(message)]
         pub fn revoke_session(&mut self, session_id: String) -> Result<()> {
-            let mut session = self.sessions.get(&session_id)
+            let session = self.sessions.get(&session_id)
                 .ok_or(Error::SessionNotFound)?;
 
+            let payer = self.session_deposits.get(&session_id).map(|deposit| deposit.payer);
+            if !self.acts_for(session.account) && payer != Some(self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+
             if !session.is_active {
                 return Err(Error::SessionAlreadyRevoked);
             }
 
-            session.is_active = false;
-            self.sessions.insert(&session_id, &session);
-            self.active_sessions = self.active_sessions.saturating_sub(1);
-
-            self.env().emit_event(SessionRevoked {
-                session_id,
-                timestamp: self.env().block_timestamp(),
-            });
+            if let Some(mut account_sessions) = self.account_sessions.get(session.account) {
+                account_sessions.retain(|id| *id != session_id);
+                self.account_sessions.insert(session.account, &account_sessions);
+            }
 
-            Ok(())
+            self.close_session(session_id, session)
         }
 
-        // ========================================
-        // IDENTITY MANAGEMENT
-        // ========================================
-
-        /// Change password for an account
+        /// Delegate a session key that may act for the caller within the given scopes
+        ///
+        /// # Arguments
+        /// * `key` - Delegated account
+        /// * `scopes` - Permission scopes, e.g. `"transfer"` or `"vote"`
+        /// * `expires_at` - Timestamp from which the key is no longer authorized
+        /// * `session_id` - Optional active session of the caller to bind the key to
         #[ink(message)]
-        pub fn change_password(
+        pub fn add_session_key(
             &mut self,
-            old_password_hash: String,
-            new_password_hash: String,
+            key: AccountId,
+            scopes: Vec<String>,
+            expires_at: u64,
+            session_id: Option<String>,
         ) -> Result<()> {
             let caller = self.env().caller();
             let timestamp = self.env().block_timestamp();
 
-            let mut identity = self.identities.get(caller)
-                .ok_or(Error::IdentityNotFound)?;
+            if !self.identities.contains(caller) {
+                return Err(Error::IdentityNotFound);
+            }
 
-            // Verify old password
-            if identity.password_hash != old_password_hash {
-                return Err(Error::InvalidCredentials);
+            self.validate_scopes(&scopes)?;
+
+            if is_expired(timestamp, expires_at) {
+                return Err(Error::InvalidExpiry);
             }
 
-            // Validate new password hash
-            self.validate_password_hash(&new_password_hash)?;
+            if let Some(session_id) = &session_id {
+                if self.check_session(session_id)?.account != caller {
+                    return Err(Error::Unauthorized);
+                }
+            }
 
-            // Update password
-            identity.password_hash = new_password_hash;
-            self.identities.insert(caller, &identity);
+            let mut keys = self.session_keys.get(caller).unwrap_or_default();
+            if keys.iter().any(|k| k.key == key) {
+                return Err(Error::SessionKeyAlreadyExists);
+            }
+            if keys.len() >= MAX_SESSION_KEYS {
+                return Err(Error::TooManySessionKeys);
+            }
 
-            self.env().emit_event(PasswordChanged {
-                account: caller,
-                timestamp,
+            keys.push(SessionKey {
+                key,
+                scopes: scopes.clone(),
+                created_at: timestamp,
+                expires_at,
+                session_id: session_id.clone(),
+            });
+            self.session_keys.insert(caller, &keys);
+
+            if let Some(session_id) = &session_id {
+                let mut linked = self.session_key_links.get(session_id).unwrap_or_default();
+                linked.push(key);
+                self.session_key_links.insert(session_id, &linked);
+            }
+
+            self.env().emit_event(SessionKeyAdded {
+                owner: caller,
+                key,
+                scopes,
+                expires_at,
+                session_id,
             });
 
             Ok(())
         }
 
-        /// Unlock a locked account (admin only or after timeout)
+        /// Revoke one of the caller's session keys
         #[ink(message)]
-        pub fn unlock_account(&mut self, account: AccountId) -> Result<()> {
+        pub fn revoke_session_key(&mut self, key: AccountId) -> Result<()> {
             let caller = self.env().caller();
-            let timestamp = self.env().block_timestamp();
 
-            // Only admin can manually unlock
-            if caller != self.admin {
-                return Err(Error::Unauthorized);
+            let session_key = self.session_keys.get(caller)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|k| k.key == key)
+                .ok_or(Error::SessionKeyNotFound)?;
+
+            if let Some(session_id) = session_key.session_id {
+                let mut linked = self.session_key_links.get(&session_id).unwrap_or_default();
+                linked.retain(|k| *k != key);
+                if linked.is_empty() {
+                    self.session_key_links.remove(&session_id);
+                } else {
+                    self.session_key_links.insert(&session_id, &linked);
+                }
             }
 
-            let mut identity = self.identities.get(account)
-                .ok_or(Error::IdentityNotFound)?;
+            self.drop_session_key(caller, key);
 
-            identity.is_locked = false;
-            identity.failed_attempts = 0;
-            self.identities.insert(account, &identity);
+            Ok(())
+        }
 
-            self.env().emit_event(AccountUnlocked {
-                account,
-                timestamp,
+        // ========================================
+        // CLIENT APPLICATIONS
+        // ========================================
+
+        /// Register a client application owned by the caller
+        ///
+        /// Each account can register at most `MAX_CLIENTS_PER_OWNER` clients; disabled
+        /// clients keep counting. Client authentication (secrets, signed requests)
+        /// happens off-chain between the client and its backend.
+        ///
+        /// # Arguments
+        /// * `name` - Application name
+        /// * `redirect_uris` - Allowed redirect URIs
+        ///
+        /// # Returns
+        /// * `Ok(u64)` - ID of the new client
+        #[ink(message)]
+        pub fn register_client(&mut self, name: String, redirect_uris: Vec<String>) -> Result<u64> {
+            let caller = self.env().caller();
+
+            self.validate_client(&name, &redirect_uris)?;
+
+            let client_count = self.client_counts.get(caller).unwrap_or(0);
+            if client_count >= MAX_CLIENTS_PER_OWNER {
+                return Err(Error::TooManyClients);
+            }
+            self.client_counts.insert(caller, &client_count.saturating_add(1));
+
+            let client_id = self.next_client_id;
+            self.next_client_id = self.next_client_id.saturating_add(1);
+
+            self.clients.insert(client_id, &ClientApp {
+                client_id,
+                owner: caller,
+                name: name.clone(),
+                redirect_uris,
+                enabled: true,
+                created_at: self.env().block_timestamp(),
             });
 
-            Ok(())
+            self.env().emit_event(ClientRegistered {
+                client_id,
+                owner: caller,
+                name,
+            });
+
+            Ok(client_id)
         }
 
-        /// Verify an identity (mark as verified by admin)
+        /// Update a client application (owner only)
         #[ink(message)]
-        pub fn verify_identity(&mut self, account: AccountId) -> Result<()> {
+        pub fn update_client(&mut self, client_id: u64, name: String, redirect_uris: Vec<String>) -> Result<()> {
             let caller = self.env().caller();
-            let timestamp = self.env().block_timestamp();
 
-            // Only admin can verify
-            if caller != self.admin {
+            let mut client = self.clients.get(client_id)
+                .ok_or(Error::ClientNotFound)?;
+
+            if caller != client.owner {
                 return Err(Error::Unauthorized);
             }
 
-            let mut identity = self.identities.get(account)
-                .ok_or(Error::IdentityNotFound)?;
+            self.validate_client(&name, &redirect_uris)?;
 
-            identity.is_verified = true;
-            self.identities.insert(account, &identity);
+            client.name = name;
+            client.redirect_uris = redirect_uris;
+            self.clients.insert(client_id, &client);
 
-            self.env().emit_event(IdentityVerified {
-                account,
-                verified_by: caller,
-                timestamp,
+            self.env().emit_event(ClientUpdated {
+                client_id,
+                timestamp: self.env().block_timestamp(),
             });
 
             Ok(())
         }
 
-        // ========================================
-        // QUERY FUNCTIONS (Read-only)
-        // ========================================
-
-        /// Get complete identity information for an account
+        /// Disable a client application (owner or admin)
+        ///
+        /// Sessions scoped to a disabled client no longer verify.
         #[ink(message)]
-        pub fn get_identity(&self, account: AccountId) -> Option<IdentityInfo> {
-            self.identities.get(account)
-        }
+        pub fn disable_client(&mut self, client_id: u64) -> Result<()> {
+            let caller = self.env().caller();
 
-        /// Get account address by username
-        #[ink(message)]
-        pub fn get_account_by_username(&self, username: String) -> Option<AccountId> {
-            let username_lower = username.to_lowercase();
-            self.username_to_account.get(&username_lower)
-        }
+            let mut client = self.clients.get(client_id)
+                .ok_or(Error::ClientNotFound)?;
 
-        /// Get account by social ID hash
-        #[ink(message)]
-        pub fn get_account_by_social(&self, social_id_hash: String) -> Option<AccountId> {
-            self.social_to_account.get(&social_id_hash)
-        }
+            if caller != client.owner && caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
 
-        /// Check if username is available
-        #[ink(message)]
-        pub fn is_username_available(&self, username: String) -> bool {
-            let username_lower = username.to_lowercase();
-            !self.username_to_account.contains(&username_lower)
-        }
+            if !client.enabled {
+                return Err(Error::ClientDisabled);
+            }
 
-        /// Check if social ID is already bound
-        #[ink(message)]
-        pub fn is_social_id_available(&self, social_id_hash: String) -> bool {
-            !self.social_to_account.contains(&social_id_hash)
-        }
+            client.enabled = false;
+            self.clients.insert(client_id, &client);
 
-        /// Check if account has registered identity
-        #[ink(message)]
-        pub fn has_identity(&self, account: AccountId) -> bool {
-            self.identities.contains(account)
-        }
+            self.env().emit_event(ClientDisabled {
+                client_id,
+                timestamp: self.env().block_timestamp(),
+            });
 
-        /// Get total number of registered users
-        #[ink(message)]
-        pub fn get_total_users(&self) -> u64 {
-            self.total_users
+            Ok(())
         }
 
-        /// Get number of active sessions
+        /// Consent to share `scopes` with a client application until `expires_at`
+        ///
+        /// Replaces an earlier grant to the same client. Updating a grant that is still
+        /// valid keeps its `granted_at`; a new grant after a revoke or expiry starts
+        /// fresh, so sessions issued under the old grant stay invalid.
         #[ink(message)]
-        pub fn get_active_sessions(&self) -> u64 {
-            self.active_sessions
-        }
+        pub fn grant_consent(&mut self, client_id: u64, scopes: Vec<String>, expires_at: u64) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
 
-        /// Get contract admin address
-        #[ink(message)]
-        pub fn get_admin(&self) -> AccountId {
-            self.admin
-        }
+            if !self.identities.contains(caller) {
+                return Err(Error::IdentityNotFound);
+            }
 
-        /// Get max failed attempts setting
-        #[ink(message)]
-        pub fn get_max_failed_attempts(&self) -> u32 {
-            self.max_failed_attempts
+            self.ensure_client_enabled(client_id)?;
+            self.validate_scopes(&scopes)?;
+
+            if is_expired(timestamp, expires_at) {
+                return Err(Error::InvalidExpiry);
+            }
+
+            let granted_at = self.valid_consent(caller, client_id)
+                .map_or(timestamp, |grant| grant.granted_at);
+
+            let mut consents = self.consents.get(caller).unwrap_or_default();
+            consents.retain(|grant| grant.client_id != client_id);
+            if consents.len() >= MAX_CONSENTS {
+                return Err(Error::TooManyConsents);
+            }
+
+            consents.push(ConsentGrant {
+                client_id,
+                scopes: scopes.clone(),
+                granted_at,
+                expires_at,
+            });
+            self.consents.insert(caller, &consents);
+
+            self.env().emit_event(ConsentGranted {
+                account: caller,
+                client_id,
+                scopes,
+                expires_at,
+            });
+
+            Ok(())
         }
 
-        /// Get lockout duration setting
+        /// Withdraw consent from a client application
+        ///
+        /// Sessions scoped to the client are revoked, so granting consent again does
+        /// not bring them back.
         #[ink(message)]
-        pub fn get_lockout_duration(&self) -> u64 {
-            self.lockout_duration
+        pub fn revoke_consent(&mut self, client_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            let mut consents = self.consents.get(caller).unwrap_or_default();
+            let position = consents.iter().position(|grant| grant.client_id == client_id)
+                .ok_or(Error::ConsentNotFound)?;
+            consents.swap_remove(position);
+
+            if consents.is_empty() {
+                self.consents.remove(caller);
+            } else {
+                self.consents.insert(caller, &consents);
+            }
+
+            let mut kept = Vec::new();
+            for session_id in self.account_sessions.get(caller).unwrap_or_default() {
+                match self.sessions.get(&session_id) {
+                    Some(session) if session.is_active && session.client_id == Some(client_id) => {
+                        self.close_session(session_id, session)?;
+                    }
+                    _ => kept.push(session_id),
+                }
+            }
+            if kept.is_empty() {
+                self.account_sessions.remove(caller);
+            } else {
+                self.account_sessions.insert(caller, &kept);
+            }
+
+            self.env().emit_event(ConsentRevoked {
+                account: caller,
+                client_id,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
         }
 
         // ========================================
-        // ADMIN FUNCTIONS
+        // IDENTITY MANAGEMENT
         // ========================================
 
-        /// Transfer admin role to another account
+        /// Set or replace a profile attribute commitment
+        ///
+        /// # Arguments
+        /// * `key` - Attribute name
+        /// * `commitment` - Output of `compute_attribute_commitment` for the caller
         #[ink(message)]
-        pub fn transfer_admin(&mut self, new_admin: AccountId) -> Result<()> {
+        pub fn set_attribute(&mut self, key: String, commitment: [u8; 32]) -> Result<()> {
             let caller = self.env().caller();
 
-            if caller != self.admin {
-                return Err(Error::Unauthorized);
+            if !self.identities.contains(caller) {
+                return Err(Error::IdentityNotFound);
             }
 
-            self.admin = new_admin;
+            if key.is_empty() || key.len() > MAX_ATTRIBUTE_KEY_LEN {
+                return Err(Error::InvalidAttributeKey);
+            }
+
+            let mut attributes = self.attributes.get(caller).unwrap_or_default();
+            attributes.retain(|attribute| attribute.key != key);
+            if attributes.len() >= MAX_ATTRIBUTES {
+                return Err(Error::TooManyAttributes);
+            }
+
+            attributes.push(AttributeCommitment {
+                key: key.clone(),
+                commitment,
+                updated_at: self.env().block_timestamp(),
+            });
+            self.attributes.insert(caller, &attributes);
+
+            self.env().emit_event(AttributeSet {
+                account: caller,
+                key,
+                commitment,
+            });
+
             Ok(())
         }
 
-        /// Update max failed attempts setting
+        /// Remove a profile attribute
         #[ink(message)]
-        pub fn update_max_failed_attempts(&mut self, new_max: u32) -> Result<()> {
+        pub fn remove_attribute(&mut self, key: String) -> Result<()> {
             let caller = self.env().caller();
 
-            if caller != self.admin {
-                return Err(Error::Unauthorized);
+            let mut attributes = self.attributes.get(caller).unwrap_or_default();
+            let position = attributes.iter().position(|attribute| attribute.key == key)
+                .ok_or(Error::AttributeNotFound)?;
+            attributes.swap_remove(position);
+
+            if attributes.is_empty() {
+                self.attributes.remove(caller);
+            } else {
+                self.attributes.insert(caller, &attributes);
             }
 
-            self.max_failed_attempts = new_max;
+            self.env().emit_event(AttributeRemoved {
+                account: caller,
+                key,
+            });
+
             Ok(())
         }
 
-        /// Update lockout duration setting
+        /// Change password for an account
         #[ink(message)]
-        pub fn update_lockout_duration(&mut self, new_duration: u64) -> Result<()> {
+        pub fn change_password(
+            &mut self,
+            old_password_hash: String,
+            new_password_hash: String,
+        ) -> Result<()> {
             let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
 
-            if caller != self.admin {
-                return Err(Error::Unauthorized);
+            let mut identity = self.identities.get(caller)
+                .ok_or(Error::IdentityNotFound)?;
+
+            // Verify old password
+            if identity.password_hash != old_password_hash {
+                return Err(Error::InvalidCredentials);
             }
 
-            self.lockout_duration = new_duration;
+            // Validate new password hash
+            self.validate_password_hash(&new_password_hash)?;
+
+            // Update password
+            identity.password_hash = new_password_hash;
+            self.identities.insert(caller, &identity);
+
+            self.env().emit_event(PasswordChanged {
+                account: caller,
+                timestamp,
+            });
+            self.record_audit(caller, AuditAction::PasswordChanged);
+
             Ok(())
         }
 
-        // ========================================
-        // VALIDATION HELPERS (Private)
-        // ========================================
+        /// Delete the caller's identity and refund its registration deposit
+        ///
+        /// Every unrevoked session of the caller is revoked and its deposit refunded.
+        #[ink(message)]
+        pub fn delete_identity(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
 
-        fn validate_username(&self, username: &str) -> Result<()> {
-            if username.is_empty() {
-                return Err(Error::EmptyUsername);
+            let identity = self.identities.get(caller)
+                .ok_or(Error::IdentityNotFound)?;
+
+            self.identities.remove(caller);
+            self.username_to_account.remove(identity.username.to_lowercase());
+            self.remove_from_prefix_bucket(&identity.username.to_lowercase());
+            self.social_to_account.remove(&identity.social_id_hash);
+            self.verifications.remove(caller);
+            self.total_users = self.total_users.saturating_sub(1);
+
+            // Swap-remove from the identity index
+            if let Some(index) = self.identity_index_position.take(caller) {
+                if let Some(last) = self.identity_index.pop() {
+                    if last != caller {
+                        self.identity_index.set(index, &last);
+                        self.identity_index_position.insert(last, &index);
+                    }
+                }
             }
 
-            if username.len() < 3 {
-                return Err(Error::UsernameTooShort);
+            if let Some(request_id) = self.pending_request_of.get(caller) {
+                self.resolve_request(request_id, RequestStatus::Cancelled, caller, None)?;
             }
 
-            if username.len() > 32 {
-                return Err(Error::UsernameTooLong);
+            self.did_controllers.remove(caller);
+            self.did_keys.remove(caller);
+            self.did_services.remove(caller);
+
+            for passkey in self.passkeys.take(caller).unwrap_or_default() {
+                self.passkey_owner.remove(&passkey.credential_id);
             }
 
-            // Check if username contains only alphanumeric and underscore
-            if !username.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                return Err(Error::InvalidUsernameFormat);
+            for session_id in self.account_sessions.take(caller).unwrap_or_default() {
+                if let Some(session) = self.sessions.get(&session_id) {
+                    if session.is_active {
+                        self.close_session(session_id, session)?;
+                    }
+                }
             }
 
-            Ok(())
-        }
+            self.two_factor_anchors.remove(caller);
+            self.pending_logins.remove(caller);
+            self.recovery_codes.remove(caller);
+            self.consents.remove(caller);
+            self.attributes.remove(caller);
 
-        fn validate_password_hash(&self, password_hash: &str) -> Result<()> {
-            if password_hash.is_empty() {
-                return Err(Error::EmptyPasswordHash);
+            for session_key in self.session_keys.take(caller).unwrap_or_default() {
+                if let Some(session_id) = session_key.session_id {
+                    self.session_key_links.remove(&session_id);
+                }
             }
 
-            // Allow any non-empty password hash for flexibility
-            // In production, you might want stricter validation
-            if password_hash.len() < 4 {
-                return Err(Error::EmptyPasswordHash);
+            let refunded = self.identity_deposits.take(caller).unwrap_or(0);
+            if refunded > 0 {
+                self.refund(caller, refunded)?;
             }
 
+            self.env().emit_event(IdentityDeleted {
+                account: caller,
+                username: identity.username,
+                refunded,
+                timestamp,
+            });
+
             Ok(())
         }
 
-        fn validate_social_id_hash(&self, social_id_hash: &str) -> Result<()> {
-            if social_id_hash.is_empty() {
-                return Err(Error::EmptySocialIdHash);
+        /// Unlock a locked account (admin only or after timeout)
+        #[ink(message)]
+        pub fn unlock_account(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+
+            // Only admin can manually unlock
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
             }
 
-            Ok(())
+            self.apply_unlock(account)
         }
-    }
 
-    // ========================================
-    // UNIT TESTS
-    // ========================================
+        /// Unlock several accounts (admin only)
+        ///
+        /// Emits `AccountUnlocked` for every unlocked account.
+        ///
+        /// # Returns
+        /// * `Ok(Vec<Result<()>>)` - Outcome per account, in input order
+        #[ink(message)]
+        pub fn unlock_accounts(&mut self, accounts: Vec<AccountId>) -> Result<Vec<Result<()>>> {
+            let caller = self.env().caller();
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
 
-        fn create_test_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
-            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
-        }
+            self.ensure_batch_size(accounts.len())?;
 
-        fn set_sender(sender: AccountId) {
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sender);
+            Ok(accounts.into_iter().map(|account| self.apply_unlock(account)).collect())
         }
 
-        #[ink::test]
-        fn test_new_works() {
-            let authentify = Authentify::new();
-            assert_eq!(authentify.get_total_users(), 0);
-            assert_eq!(authentify.get_active_sessions(), 0);
-            assert_eq!(authentify.get_max_failed_attempts(), 5);
-        }
+        /// Verify an identity at a given level (admin only)
+        ///
+        /// # Arguments
+        /// * `account` - Account to verify
+        /// * `level` - Verification tier granted
+        /// * `evidence_hash` - Hash of the off-chain evidence
+        /// * `expires_at` - Optional timestamp after which the verification lapses
+        ///
+        /// Replaces any previous verification of the account. This is the approve
+        /// path for verification requests: a pending request of the account for the
+        /// same level is marked approved, one for another level is cancelled.
+        #[ink(message)]
+        pub fn verify_identity(
+            &mut self,
+            account: AccountId,
+            level: VerificationLevel,
+            evidence_hash: [u8; 32],
+            expires_at: Option<u64>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
 
-        #[ink::test]
-        fn test_register_identity_works() {
-            let accounts = create_test_accounts();
-            set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            // Only admin or verifiers can verify
+            self.ensure_verifier(caller)?;
 
-            let result = authentify.register_identity(
-                String::from("alice"),
-                String::from("$2a$10$N9qo8uLOickgx2ZMRZoMyeIjZAgcfl7p92ldGxad68LJZdL17lhWy"),
-                String::from("social_hash_123"),
+            if let Some(expiry) = expires_at {
+                if expiry <= timestamp {
+                    return Err(Error::InvalidExpiry);
+                }
+            }
+
+            self.apply_verification(caller, account, level, evidence_hash, expires_at)
+        }
+
+        /// Verify several accounts at the same level (admin or verifier)
+        ///
+        /// Emits the same events as `verify_identity` for every verified account.
+        ///
+        /// # Returns
+        /// * `Ok(Vec<Result<()>>)` - Outcome per account, in input order
+        /// * `Err(Error)` if the caller is not allowed, the expiry is invalid or
+        ///   the batch exceeds `MAX_BATCH_SIZE`
+        #[ink(message)]
+        pub fn verify_identities(
+            &mut self,
+            accounts: Vec<AccountId>,
+            level: VerificationLevel,
+            evidence_hash: [u8; 32],
+            expires_at: Option<u64>,
+        ) -> Result<Vec<Result<()>>> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
+
+            self.ensure_verifier(caller)?;
+            self.ensure_batch_size(accounts.len())?;
+
+            if let Some(expiry) = expires_at {
+                if expiry <= timestamp {
+                    return Err(Error::InvalidExpiry);
+                }
+            }
+
+            Ok(accounts
+                .into_iter()
+                .map(|account| self.apply_verification(caller, account, level, evidence_hash, expires_at))
+                .collect())
+        }
+
+        /// Revoke an identity's verification (admin or verifier)
+        #[ink(message)]
+        pub fn revoke_verification(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
+
+            self.ensure_verifier(caller)?;
+
+            if !self.identities.contains(account) {
+                return Err(Error::IdentityNotFound);
+            }
+
+            if self.verifications.take(account).is_none() {
+                return Err(Error::NotVerified);
+            }
+
+            self.env().emit_event(VerificationRevoked {
+                account,
+                revoked_by: caller,
+                timestamp,
+            });
+
+            Ok(())
+        }
+
+        // ========================================
+        // VERIFICATION REQUESTS
+        // ========================================
+
+        /// Ask to be verified at a given level
+        ///
+        /// # Arguments
+        /// * `level` - Requested verification tier
+        /// * `evidence_hash` - Hash of the evidence shared with verifiers off-chain
+        #[ink(message)]
+        pub fn request_verification(
+            &mut self,
+            level: VerificationLevel,
+            evidence_hash: [u8; 32],
+        ) -> Result<u64> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
+
+            if !self.identities.contains(caller) {
+                return Err(Error::IdentityNotFound);
+            }
+
+            if self.pending_request_of.contains(caller) {
+                return Err(Error::RequestAlreadyPending);
+            }
+
+            let request_id = self.next_request_id;
+            self.next_request_id = self.next_request_id.saturating_add(1);
+
+            self.verification_requests.insert(request_id, &VerificationRequest {
+                request_id,
+                account: caller,
+                level,
+                evidence_hash,
+                status: RequestStatus::Pending,
+                created_at: timestamp,
+                resolved_by: None,
+                resolved_at: None,
+                reason: None,
+            });
+            self.pending_request_of.insert(caller, &request_id);
+            self.pending_request_index.insert(request_id, &self.pending_requests.len());
+            self.pending_requests.push(&request_id);
+
+            self.env().emit_event(VerificationRequested {
+                account: caller,
+                request_id,
+                level,
+                timestamp,
+            });
+
+            Ok(request_id)
+        }
+
+        /// Approve a pending request at its requested level (admin or verifier)
+        #[ink(message)]
+        pub fn approve_verification_request(
+            &mut self,
+            request_id: u64,
+            expires_at: Option<u64>,
+        ) -> Result<()> {
+            let request = self.verification_requests.get(request_id)
+                .ok_or(Error::RequestNotFound)?;
+
+            if request.status != RequestStatus::Pending {
+                return Err(Error::RequestNotPending);
+            }
+
+            self.verify_identity(request.account, request.level, request.evidence_hash, expires_at)
+        }
+
+        /// Reject a pending request with a reason (admin or verifier)
+        #[ink(message)]
+        pub fn reject_verification_request(&mut self, request_id: u64, reason: String) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_verifier(caller)?;
+
+            self.resolve_request(request_id, RequestStatus::Rejected, caller, Some(reason))
+        }
+
+        /// Grant or remove the verifier role (admin only)
+        #[ink(message)]
+        pub fn set_verifier(&mut self, verifier: AccountId, enabled: bool) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            if enabled {
+                self.verifiers.insert(verifier, &());
+            } else {
+                self.verifiers.remove(verifier);
+            }
+
+            self.env().emit_event(VerifierUpdated {
+                verifier,
+                enabled,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        // ========================================
+        // TWO-FACTOR AUTHENTICATION
+        // ========================================
+
+        /// Require a second factor on every password login
+        ///
+        /// The factor is a one-time hash chain, not TOTP: verifying TOTP needs the
+        /// shared secret, which public contract storage cannot keep.
+        ///
+        /// # Arguments
+        /// * `anchor` - Enrollment commitment: `H^n(seed)` with `H` = Blake2x256,
+        ///   each later proof reveals the previous link of the chain
+        #[ink(message)]
+        pub fn enable_two_factor(&mut self, anchor: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+
+            let mut identity = self.identities.get(caller)
+                .ok_or(Error::IdentityNotFound)?;
+
+            if identity.two_factor_enabled {
+                return Err(Error::TwoFactorAlreadyEnabled);
+            }
+
+            identity.two_factor_enabled = true;
+            self.identities.insert(caller, &identity);
+            self.two_factor_anchors.insert(caller, &anchor);
+
+            self.env().emit_event(TwoFactorEnabled {
+                account: caller,
+                timestamp: self.env().block_timestamp(),
+            });
+            self.record_audit(caller, AuditAction::TwoFactorEnabled);
+
+            Ok(())
+        }
+
+        /// Turn off the second factor, proving possession of the current code chain
+        #[ink(message)]
+        pub fn disable_two_factor(&mut self, proof: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+
+            let mut identity = self.identities.get(caller)
+                .ok_or(Error::IdentityNotFound)?;
+
+            if !identity.two_factor_enabled {
+                return Err(Error::TwoFactorNotEnabled);
+            }
+
+            if !self.check_two_factor_proof(caller, proof) {
+                return Err(Error::InvalidTwoFactorCode);
+            }
+
+            self.clear_two_factor(caller, &mut identity);
+
+            Ok(())
+        }
+
+        /// Replace the account's recovery codes
+        ///
+        /// # Arguments
+        /// * `salt` - Fresh random salt for this set of codes
+        /// * `code_hashes` - `compute_recovery_code_hash` of each code, computed
+        ///   off-chain; an empty list removes all codes
+        /// * `two_factor_proof` - Next one-time code, required while 2FA is enabled
+        ///   since recovery codes can reset the second factor
+        ///
+        /// Codes should be high-entropy random strings: anyone can read the hashes
+        /// and the salt from storage.
+        #[ink(message)]
+        pub fn set_recovery_codes(
+            &mut self,
+            salt: [u8; 32],
+            code_hashes: Vec<[u8; 32]>,
+            two_factor_proof: Option<[u8; 32]>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            let identity = self.identities.get(caller)
+                .ok_or(Error::IdentityNotFound)?;
+
+            if code_hashes.len() > MAX_RECOVERY_CODES {
+                return Err(Error::TooManyRecoveryCodes);
+            }
+
+            if identity.two_factor_enabled {
+                let proof = two_factor_proof.ok_or(Error::InvalidTwoFactorCode)?;
+                if !self.check_two_factor_proof(caller, proof) {
+                    return Err(Error::InvalidTwoFactorCode);
+                }
+            }
+
+            let count = code_hashes.len() as u32;
+            if code_hashes.is_empty() {
+                self.recovery_codes.remove(caller);
+            } else {
+                self.recovery_codes.insert(caller, &RecoveryCodes { salt, hashes: code_hashes });
+            }
+
+            self.env().emit_event(RecoveryCodesSet {
+                account: caller,
+                count,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        /// Burn a recovery code to reset the second factor or unlock the account
+        ///
+        /// # Arguments
+        /// * `code` - Plaintext recovery code
+        /// * `action` - What the code is used for
+        #[ink(message)]
+        pub fn consume_recovery_code(&mut self, code: String, action: RecoveryAction) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
+
+            let mut identity = self.identities.get(caller)
+                .ok_or(Error::IdentityNotFound)?;
+
+            match action {
+                RecoveryAction::ResetTwoFactor if !identity.two_factor_enabled => {
+                    return Err(Error::TwoFactorNotEnabled);
+                }
+                RecoveryAction::UnlockAccount if !self.is_lock_active(&identity, timestamp) => {
+                    return Err(Error::AccountNotLocked);
+                }
+                _ => {}
+            }
+
+            let mut codes = self.recovery_codes.get(caller)
+                .ok_or(Error::InvalidRecoveryCode)?;
+            let code_hash = compute_recovery_code_hash(caller, codes.salt, &code);
+            let position = codes.hashes.iter().position(|h| *h == code_hash)
+                .ok_or(Error::InvalidRecoveryCode)?;
+            codes.hashes.swap_remove(position);
+
+            let remaining = codes.hashes.len() as u32;
+            if codes.hashes.is_empty() {
+                self.recovery_codes.remove(caller);
+            } else {
+                self.recovery_codes.insert(caller, &codes);
+            }
+
+            match action {
+                RecoveryAction::ResetTwoFactor => self.clear_two_factor(caller, &mut identity),
+                RecoveryAction::UnlockAccount => {
+                    identity.is_locked = false;
+                    identity.failed_attempts = 0;
+                    self.identities.insert(caller, &identity);
+
+                    self.env().emit_event(AccountUnlocked {
+                        account: caller,
+                        timestamp,
+                    });
+                    self.record_audit(caller, AuditAction::AccountUnlocked);
+                }
+            }
+
+            self.env().emit_event(RecoveryCodeConsumed {
+                account: caller,
+                action,
+                remaining,
+                timestamp,
+            });
+            self.record_audit(caller, AuditAction::RecoveryCodeUsed(action));
+
+            Ok(())
+        }
+
+        // ========================================
+        // PASSKEYS
+        // ========================================
+
+        /// Register a WebAuthn/passkey credential for the caller's identity
+        ///
+        /// # Arguments
+        /// * `credential_id` - WebAuthn credential ID from the attestation
+        /// * `public_key` - COSE-encoded ES256 (P-256) public key
+        /// * `rp_id_hash` - SHA-256 of the relying party ID
+        /// * `label` - User-facing name for the authenticator
+        #[ink(message)]
+        pub fn add_passkey(
+            &mut self,
+            credential_id: Vec<u8>,
+            public_key: Vec<u8>,
+            rp_id_hash: [u8; 32],
+            label: String,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
+
+            if !self.identities.contains(caller) {
+                return Err(Error::IdentityNotFound);
+            }
+
+            if credential_id.is_empty() || credential_id.len() > MAX_CREDENTIAL_ID_LEN {
+                return Err(Error::InvalidCredentialId);
+            }
+
+            if label.len() > MAX_PASSKEY_LABEL_LEN {
+                return Err(Error::PasskeyLabelTooLong);
+            }
+
+            webauthn::parse_cose_p256_key(&public_key)?;
+
+            if self.passkey_owner.contains(&credential_id) {
+                return Err(Error::PasskeyAlreadyRegistered);
+            }
+
+            let mut passkeys = self.passkeys.get(caller).unwrap_or_default();
+            if passkeys.len() >= MAX_PASSKEYS as usize {
+                return Err(Error::TooManyPasskeys);
+            }
+
+            passkeys.push(PasskeyCredential {
+                credential_id: credential_id.clone(),
+                public_key,
+                rp_id_hash,
+                sign_count: 0,
+                label: label.clone(),
+                created_at: timestamp,
+                last_used: 0,
+            });
+            self.passkeys.insert(caller, &passkeys);
+            self.passkey_owner.insert(&credential_id, &caller);
+
+            self.env().emit_event(PasskeyAdded {
+                account: caller,
+                credential_id,
+                label,
+                timestamp,
+            });
+            self.record_audit(caller, AuditAction::PasskeyAdded);
+
+            Ok(())
+        }
+
+        /// Remove one of the caller's passkeys
+        #[ink(message)]
+        pub fn remove_passkey(&mut self, credential_id: Vec<u8>) -> Result<()> {
+            let caller = self.env().caller();
+
+            if self.passkey_owner.get(&credential_id) != Some(caller) {
+                return Err(Error::PasskeyNotFound);
+            }
+
+            let mut passkeys = self.passkeys.get(caller).unwrap_or_default();
+            passkeys.retain(|passkey| passkey.credential_id != credential_id);
+            if passkeys.is_empty() {
+                self.passkeys.remove(caller);
+            } else {
+                self.passkeys.insert(caller, &passkeys);
+            }
+            self.passkey_owner.remove(&credential_id);
+
+            self.env().emit_event(PasskeyRemoved {
+                account: caller,
+                credential_id,
+                timestamp: self.env().block_timestamp(),
+            });
+            self.record_audit(caller, AuditAction::PasskeyRemoved);
+
+            Ok(())
+        }
+
+        // ========================================
+        // CREDENTIALS
+        // ========================================
+
+        /// Anchor a credential hash for a subject identity (issuers only)
+        ///
+        /// # Arguments
+        /// * `subject` - Registered identity the credential is about
+        /// * `credential_hash` - Hash of the off-chain credential document
+        /// * `credential_type` - Credential type (e.g. "KycCredential")
+        /// * `expires_at` - Optional timestamp after which the credential lapses
+        #[ink(message)]
+        pub fn issue_credential(
+            &mut self,
+            subject: AccountId,
+            credential_hash: [u8; 32],
+            credential_type: String,
+            expires_at: Option<u64>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
+
+            if !self.issuers.contains(caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if credential_type.is_empty() {
+                return Err(Error::EmptyCredentialType);
+            }
+
+            if credential_type.len() > MAX_CREDENTIAL_TYPE_LEN {
+                return Err(Error::CredentialTypeTooLong);
+            }
+
+            if let Some(expiry) = expires_at {
+                if expiry <= timestamp {
+                    return Err(Error::InvalidExpiry);
+                }
+            }
+
+            if !self.identities.contains(subject) {
+                return Err(Error::IdentityNotFound);
+            }
+
+            if self.credentials.contains(credential_hash) {
+                return Err(Error::CredentialAlreadyExists);
+            }
+
+            self.credentials.insert(credential_hash, &CredentialRecord {
+                credential_hash,
+                issuer: caller,
+                subject,
+                credential_type: credential_type.clone(),
+                issued_at: timestamp,
+                expires_at,
+                revoked_at: None,
+            });
+
+            self.env().emit_event(CredentialIssued {
+                subject,
+                issuer: caller,
+                credential_hash,
+                credential_type,
+                expires_at,
+                timestamp,
+            });
+
+            Ok(())
+        }
+
+        /// Revoke a credential (its issuer or the admin)
+        #[ink(message)]
+        pub fn revoke_credential(&mut self, credential_hash: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+            let timestamp = self.env().block_timestamp();
+
+            let mut credential = self.credentials.get(credential_hash)
+                .ok_or(Error::CredentialNotFound)?;
+
+            if caller != credential.issuer && caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            if credential.revoked_at.is_some() {
+                return Err(Error::CredentialAlreadyRevoked);
+            }
+
+            credential.revoked_at = Some(timestamp);
+            self.credentials.insert(credential_hash, &credential);
+
+            self.env().emit_event(CredentialRevoked {
+                subject: credential.subject,
+                issuer: credential.issuer,
+                credential_hash,
+                timestamp,
+            });
+
+            Ok(())
+        }
+
+        /// Grant or remove the credential issuer role (admin only)
+        #[ink(message)]
+        pub fn set_issuer(&mut self, issuer: AccountId, enabled: bool) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            if enabled {
+                self.issuers.insert(issuer, &());
+            } else {
+                self.issuers.remove(issuer);
+            }
+
+            self.env().emit_event(IssuerUpdated {
+                issuer,
+                enabled,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        // ========================================
+        // DID MANAGEMENT
+        // ========================================
+
+        /// Add a verification method to a DID document (controller only)
+        ///
+        /// # Arguments
+        /// * `subject` - Account whose DID is updated
+        /// * `key_id` - Fragment for the key, unique within the document
+        /// * `key_type` - Signature scheme of the key
+        /// * `public_key` - Raw public key bytes
+        #[ink(message)]
+        pub fn add_did_key(
+            &mut self,
+            subject: AccountId,
+            key_id: String,
+            key_type: KeyType,
+            public_key: Vec<u8>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_did_controller(subject, caller)?;
+            self.validate_did_fragment(&key_id)?;
+
+            if public_key.is_empty() || public_key.len() > MAX_DID_PUBLIC_KEY_LEN {
+                return Err(Error::InvalidDidEntry);
+            }
+
+            let mut keys = self.did_keys.get(subject).unwrap_or_default();
+
+            if keys.iter().any(|key| key.id == key_id) {
+                return Err(Error::DidEntryAlreadyExists);
+            }
+
+            if keys.len() >= MAX_DID_KEYS as usize {
+                return Err(Error::TooManyDidEntries);
+            }
+
+            keys.push(VerificationMethod {
+                id: key_id.clone(),
+                key_type,
+                controller: caller,
+                public_key,
+            });
+            self.did_keys.insert(subject, &keys);
+
+            self.env().emit_event(DidKeyAdded {
+                subject,
+                key_id,
+                key_type,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        /// Remove a verification method from a DID document (controller only)
+        #[ink(message)]
+        pub fn remove_did_key(&mut self, subject: AccountId, key_id: String) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_did_controller(subject, caller)?;
+
+            let mut keys = self.did_keys.get(subject).unwrap_or_default();
+            let index = keys.iter().position(|key| key.id == key_id)
+                .ok_or(Error::DidEntryNotFound)?;
+
+            keys.remove(index);
+            self.did_keys.insert(subject, &keys);
+
+            self.env().emit_event(DidKeyRemoved {
+                subject,
+                key_id,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        /// Add a service endpoint to a DID document (controller only)
+        #[ink(message)]
+        pub fn add_did_service(
+            &mut self,
+            subject: AccountId,
+            service_id: String,
+            service_type: String,
+            endpoint: String,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_did_controller(subject, caller)?;
+            self.validate_did_fragment(&service_id)?;
+
+            if service_type.is_empty() || service_type.len() > MAX_DID_SERVICE_TYPE_LEN {
+                return Err(Error::InvalidDidEntry);
+            }
+
+            if endpoint.is_empty() || endpoint.len() > MAX_DID_ENDPOINT_LEN {
+                return Err(Error::InvalidDidEntry);
+            }
+
+            let mut services = self.did_services.get(subject).unwrap_or_default();
+
+            if services.iter().any(|service| service.id == service_id) {
+                return Err(Error::DidEntryAlreadyExists);
+            }
+
+            if services.len() >= MAX_DID_SERVICES as usize {
+                return Err(Error::TooManyDidEntries);
+            }
+
+            services.push(ServiceEndpoint {
+                id: service_id.clone(),
+                service_type,
+                endpoint: endpoint.clone(),
+            });
+            self.did_services.insert(subject, &services);
+
+            self.env().emit_event(DidServiceAdded {
+                subject,
+                service_id,
+                endpoint,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        /// Remove a service endpoint from a DID document (controller only)
+        #[ink(message)]
+        pub fn remove_did_service(&mut self, subject: AccountId, service_id: String) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_did_controller(subject, caller)?;
+
+            let mut services = self.did_services.get(subject).unwrap_or_default();
+            let index = services.iter().position(|service| service.id == service_id)
+                .ok_or(Error::DidEntryNotFound)?;
+
+            services.remove(index);
+            self.did_services.insert(subject, &services);
+
+            self.env().emit_event(DidServiceRemoved {
+                subject,
+                service_id,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        /// Hand control of a DID document to another account (controller only)
+        #[ink(message)]
+        pub fn change_did_controller(&mut self, subject: AccountId, new_controller: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_did_controller(subject, caller)?;
+
+            if new_controller == subject {
+                self.did_controllers.remove(subject);
+            } else {
+                self.did_controllers.insert(subject, &new_controller);
+            }
+
+            self.env().emit_event(DidControllerChanged {
+                subject,
+                new_controller,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        // ========================================
+        // QUERY FUNCTIONS (Read-only)
+        // ========================================
+
+        /// Get the public identity information for an account
+        #[ink(message)]
+        pub fn get_identity(&self, account: AccountId) -> Option<PublicIdentity> {
+            self.identities.get(account).map(|identity| self.public_identity(identity))
+        }
+
+        /// Get the complete stored identity record, hashes included
+        ///
+        /// Only the account itself or the admin gets the record; anyone else gets `None`.
+        #[ink(message)]
+        pub fn get_full_identity(&self, account: AccountId) -> Option<IdentityInfo> {
+            if !self.acts_for(account) {
+                return None;
+            }
+            self.identities.get(account)
+        }
+
+        /// Get an account's most recent audit entries, newest first
+        ///
+        /// Only the account itself or the admin gets the log; anyone else gets nothing.
+        #[ink(message)]
+        pub fn get_audit_log(&self, account: AccountId, limit: u32) -> Vec<AuditEntry> {
+            if !self.acts_for(account) {
+                return Vec::new();
+            }
+            let count = self.audit_counts.get(account).unwrap_or(0);
+            let len = count.min(u64::from(MAX_AUDIT_ENTRIES));
+            let limit = u64::from(limit).min(len);
+
+            (1..=limit)
+                .filter_map(|back| self.audit_entries.get((account, Self::audit_slot(count - back))))
+                .collect()
+        }
+
+        /// Get verification details for an account (including expired ones)
+        #[ink(message)]
+        pub fn get_verification(&self, account: AccountId) -> Option<VerificationRecord> {
+            self.verifications.get(account)
+        }
+
+        /// Get the current verification level, ignoring expired verifications
+        #[ink(message)]
+        pub fn get_verification_level(&self, account: AccountId) -> Option<VerificationLevel> {
+            let record = self.verifications.get(account)?;
+
+            match record.expires_at {
+                Some(expiry) if is_expired(self.env().block_timestamp(), expiry) => None,
+                _ => Some(record.level),
+            }
+        }
+
+        /// Check if an account holds a valid verification of at least `min_level`
+        #[ink(message)]
+        pub fn has_verification_level(&self, account: AccountId, min_level: VerificationLevel) -> bool {
+            self.get_verification_level(account)
+                .is_some_and(|level| level >= min_level)
+        }
+
+        /// Check if an account can verify identities
+        #[ink(message)]
+        pub fn is_verifier(&self, account: AccountId) -> bool {
+            account == self.admin || self.verifiers.contains(account)
+        }
+
+        /// Get a verification request by ID
+        #[ink(message)]
+        pub fn get_verification_request(&self, request_id: u64) -> Option<VerificationRequest> {
+            self.verification_requests.get(request_id)
+        }
+
+        /// Get the pending verification request of an account
+        #[ink(message)]
+        pub fn get_pending_request_of(&self, account: AccountId) -> Option<VerificationRequest> {
+            self.pending_request_of.get(account)
+                .and_then(|request_id| self.verification_requests.get(request_id))
+        }
+
+        /// Get number of pending verification requests
+        #[ink(message)]
+        pub fn get_pending_request_count(&self) -> u32 {
+            self.pending_requests.len()
+        }
+
+        /// List pending verification requests (at most `MAX_PAGE_SIZE` per call)
+        #[ink(message)]
+        pub fn list_pending_requests(&self, offset: u32, limit: u32) -> Vec<VerificationRequest> {
+            let end = offset
+                .saturating_add(limit.min(MAX_PAGE_SIZE))
+                .min(self.pending_requests.len());
+
+            (offset..end)
+                .filter_map(|index| self.pending_requests.get(index))
+                .filter_map(|request_id| self.verification_requests.get(request_id))
+                .collect()
+        }
+
+        /// Get contract-wide counters
+        #[ink(message)]
+        pub fn get_stats(&self) -> ContractStats {
+            let registrations_by_provider = (0..self.allowed_providers.len())
+                .filter_map(|index| self.allowed_providers.get(index))
+                .map(|provider| {
+                    let count = self.provider_registrations.get(&provider).unwrap_or(0);
+                    (provider, count)
+                })
+                .collect();
+
+            ContractStats {
+                total_users: self.total_users,
+                active_sessions: self.active_sessions,
+                verifications_granted: self.verifications_granted,
+                lockouts: self.lockouts,
+                total_logins: self.total_logins,
+                failed_logins: self.failed_logins,
+                sessions_created: self.sessions_created,
+                sessions_revoked: self.sessions_revoked,
+                registrations_by_provider,
+            }
+        }
+
+        /// Check if a social provider is on the registration allowlist
+        #[ink(message)]
+        pub fn is_provider_allowed(&self, provider: String) -> bool {
+            self.provider_position(&provider).is_some()
+        }
+
+        /// Get cumulative registrations for a social provider
+        #[ink(message)]
+        pub fn get_provider_registrations(&self, provider: String) -> u64 {
+            self.provider_registrations.get(&provider).unwrap_or(0)
+        }
+
+        /// Get number of entries in the identity index
+        #[ink(message)]
+        pub fn get_identity_count(&self) -> u32 {
+            self.identity_index.len()
+        }
+
+        /// List identities matching `filter`
+        ///
+        /// Examines at most `MAX_SCAN_SIZE` index entries starting at `offset` and
+        /// returns up to `limit` matches (capped at `MAX_PAGE_SIZE`). Continue with
+        /// `next_offset` to page through the rest.
+        #[ink(message)]
+        pub fn list_identities(&self, offset: u32, limit: u32, filter: IdentityFilter) -> IdentityPage {
+            let timestamp = self.env().block_timestamp();
+            let limit = limit.min(MAX_PAGE_SIZE) as usize;
+            let len = self.identity_index.len();
+            let scan_end = offset.saturating_add(MAX_SCAN_SIZE).min(len);
+
+            let mut identities = Vec::new();
+            let mut index = offset;
+            while index < scan_end && identities.len() < limit {
+                if let Some(identity) = self.identity_index.get(index)
+                    .and_then(|account| self.identities.get(account))
+                {
+                    if filter.verified.is_none_or(|verified| self.has_valid_verification(identity.wallet_address) == verified)
+                        && filter.locked.is_none_or(|locked| self.is_lock_active(&identity, timestamp) == locked)
+                        && filter.provider.as_ref().is_none_or(|provider| identity.social_provider == *provider)
+                    {
+                        identities.push(self.public_identity(identity));
+                    }
+                }
+                index = index.saturating_add(1);
+            }
+
+            IdentityPage {
+                identities,
+                next_offset: if index < len { Some(index) } else { None },
+            }
+        }
+
+        /// Find lowercase usernames starting with `prefix` (case-insensitive)
+        ///
+        /// The prefix needs at least `USERNAME_PREFIX_LEN` characters. Examines at most
+        /// `MAX_SCAN_SIZE` entries of the prefix bucket and returns up to `limit` matches
+        /// (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn search_usernames(&self, prefix: String, limit: u32) -> Result<Vec<String>> {
+            let prefix = prefix.to_lowercase();
+            if prefix.chars().count() < USERNAME_PREFIX_LEN {
+                return Err(Error::InvalidPrefix);
+            }
+
+            let bucket = prefix_bucket(&prefix);
+            let len = self.prefix_bucket_len.get(&bucket).unwrap_or(0);
+            let limit = limit.min(MAX_PAGE_SIZE) as usize;
+
+            Ok((0..len.min(MAX_SCAN_SIZE))
+                .filter_map(|index| self.prefix_bucket_entries.get((bucket.clone(), index)))
+                .filter(|username| username.starts_with(&prefix))
+                .take(limit)
+                .collect())
+        }
+
+        /// Get the login awaiting a second-factor proof for an account
+        #[ink(message)]
+        pub fn get_pending_login(&self, account: AccountId) -> Option<PendingLogin> {
+            self.pending_logins.get(account)
+        }
+
+        /// Get a client application
+        #[ink(message)]
+        pub fn get_client(&self, client_id: u64) -> Option<ClientApp> {
+            self.clients.get(client_id)
+        }
+
+        /// Get an account's commitment for one profile attribute
+        ///
+        /// A relying party handed the value and salt recomputes the commitment with
+        /// `compute_attribute_commitment` and compares it locally.
+        #[ink(message)]
+        pub fn get_attribute(&self, account: AccountId, key: String) -> Option<AttributeCommitment> {
+            self.attributes.get(account)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|attribute| attribute.key == key)
+        }
+
+        /// Get the consents an account granted to client applications
+        #[ink(message)]
+        pub fn get_consents(&self, account: AccountId) -> Vec<ConsentGrant> {
+            self.consents.get(account).unwrap_or_default()
+        }
+
+        /// Check if an account holds an unexpired consent for a client application
+        #[ink(message)]
+        pub fn has_consent(&self, account: AccountId, client_id: u64) -> bool {
+            self.valid_consent(account, client_id).is_some()
+        }
+
+        /// Check if a redirect URI is registered for an enabled client
+        #[ink(message)]
+        pub fn is_redirect_uri_allowed(&self, client_id: u64, redirect_uri: String) -> bool {
+            self.clients.get(client_id)
+                .is_some_and(|client| client.enabled && client.redirect_uris.contains(&redirect_uri))
+        }
+
+        /// Check whether `key` may act for `owner` within `scope`
+        ///
+        /// Intended for other contracts: the key must be unexpired, hold the scope,
+        /// and its bound session (if any) must still be valid.
+        #[ink(message)]
+        pub fn is_authorized(&self, owner: AccountId, key: AccountId, scope: String) -> bool {
+            let Some(session_key) = self.session_keys.get(owner)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|k| k.key == key)
+            else {
+                return false;
+            };
+
+            if is_expired(self.env().block_timestamp(), session_key.expires_at) {
+                return false;
+            }
+
+            if !session_key.scopes.contains(&scope) {
+                return false;
+            }
+
+            match session_key.session_id {
+                Some(session_id) => self.check_session(&session_id).is_ok(),
+                None => true,
+            }
+        }
+
+        /// Get the session keys delegated by an account
+        #[ink(message)]
+        pub fn get_session_keys(&self, owner: AccountId) -> Vec<SessionKey> {
+            self.session_keys.get(owner).unwrap_or_default()
+        }
+
+        /// Get the number of unused recovery codes of an account
+        #[ink(message)]
+        pub fn get_recovery_code_count(&self, account: AccountId) -> u32 {
+            self.recovery_codes.get(account).map(|codes| codes.hashes.len() as u32).unwrap_or(0)
+        }
+
+        /// Get two-factor window setting
+        #[ink(message)]
+        pub fn get_two_factor_window(&self) -> u64 {
+            self.config.two_factor_window
+        }
+
+        /// Get the passkeys registered for an account
+        #[ink(message)]
+        pub fn get_passkeys(&self, account: AccountId) -> Vec<PasskeyCredential> {
+            self.passkeys.get(account).unwrap_or_default()
+        }
+
+        /// Get the challenge the next passkey assertion of an account must sign
+        ///
+        /// Pass it to `navigator.credentials.get`; it changes after every
+        /// successful `authenticate_with_passkey`.
+        #[ink(message)]
+        pub fn get_passkey_challenge(&self, account: AccountId) -> [u8; 32] {
+            self.passkey_challenge(account, self.passkey_nonces.get(account).unwrap_or(0))
+        }
+
+        /// Check the status of a credential hash
+        #[ink(message)]
+        pub fn check_credential(&self, credential_hash: [u8; 32]) -> CredentialStatus {
+            let Some(credential) = self.credentials.get(credential_hash) else {
+                return CredentialStatus::Unknown;
+            };
+
+            if credential.revoked_at.is_some() {
+                return CredentialStatus::Revoked;
+            }
+
+            match credential.expires_at {
+                Some(expiry) if is_expired(self.env().block_timestamp(), expiry) => CredentialStatus::Expired,
+                _ => CredentialStatus::Valid,
+            }
+        }
+
+        /// Get the anchored record of a credential
+        #[ink(message)]
+        pub fn get_credential(&self, credential_hash: [u8; 32]) -> Option<CredentialRecord> {
+            self.credentials.get(credential_hash)
+        }
+
+        /// Check if an account can issue credentials
+        #[ink(message)]
+        pub fn is_issuer(&self, account: AccountId) -> bool {
+            self.issuers.contains(account)
+        }
+
+        /// Get the `did:authentify:0x<hex>` identifier of an account
+        #[ink(message)]
+        pub fn get_did(&self, account: AccountId) -> String {
+            let bytes: &[u8; 32] = account.as_ref();
+            let mut did = String::from(DID_PREFIX);
+            did.push_str("0x");
+
+            for byte in bytes {
+                did.push(char::from_digit(u32::from(byte >> 4), 16).unwrap_or('0'));
+                did.push(char::from_digit(u32::from(byte & 0x0f), 16).unwrap_or('0'));
+            }
+
+            did
+        }
+
+        /// Get the controller of an account's DID document
+        #[ink(message)]
+        pub fn get_did_controller(&self, account: AccountId) -> Option<AccountId> {
+            if !self.identities.contains(account) {
+                return None;
+            }
+
+            Some(self.did_controllers.get(account).unwrap_or(account))
+        }
+
+        /// Resolve the DID document of a registered identity
+        #[ink(message)]
+        pub fn resolve_did(&self, account: AccountId) -> Option<DidDocument> {
+            let identity = self.identities.get(account)?;
+            let controller = self.did_controllers.get(account).unwrap_or(account);
+
+            let wallet_key: &[u8; 32] = identity.wallet_address.as_ref();
+            let mut verification_methods = vec![VerificationMethod {
+                id: String::from("wallet"),
+                key_type: KeyType::Sr25519,
+                controller: identity.wallet_address,
+                public_key: wallet_key.to_vec(),
+            }];
+            verification_methods.extend(self.did_keys.get(account).unwrap_or_default());
+
+            Some(DidDocument {
+                id: self.get_did(account),
+                controller,
+                verification_methods,
+                services: self.did_services.get(account).unwrap_or_default(),
+                created_at: identity.created_at,
+            })
+        }
+
+        /// Get account address by username
+        #[ink(message)]
+        pub fn get_account_by_username(&self, username: String) -> Option<AccountId> {
+            let username_lower = username.to_lowercase();
+            self.username_to_account.get(&username_lower)
+        }
+
+        /// Get account by social ID hash
+        #[ink(message)]
+        pub fn get_account_by_social(&self, social_id_hash: String) -> Option<AccountId> {
+            self.social_to_account.get(&social_id_hash)
+        }
+
+        /// Check if username is available
+        #[ink(message)]
+        pub fn is_username_available(&self, username: String) -> bool {
+            let username_lower = username.to_lowercase();
+            !self.username_to_account.contains(&username_lower)
+        }
+
+        /// Get the public identities of several accounts (at most `MAX_BATCH_SIZE`)
+        #[ink(message)]
+        pub fn get_identities(&self, accounts: Vec<AccountId>) -> Result<Vec<Option<PublicIdentity>>> {
+            self.ensure_batch_size(accounts.len())?;
+
+            Ok(accounts.into_iter().map(|account| self.get_identity(account)).collect())
+        }
+
+        /// Check availability of several usernames (at most `MAX_BATCH_SIZE`)
+        #[ink(message)]
+        pub fn check_usernames(&self, usernames: Vec<String>) -> Result<Vec<bool>> {
+            self.ensure_batch_size(usernames.len())?;
+
+            Ok(usernames.into_iter().map(|username| self.is_username_available(username)).collect())
+        }
+
+        /// Verify several sessions for the same audience (at most `MAX_BATCH_SIZE`)
+        #[ink(message)]
+        pub fn verify_sessions(
+            &self,
+            session_ids: Vec<String>,
+            client_id: Option<u64>,
+        ) -> Result<Vec<Result<AccountId>>> {
+            self.ensure_batch_size(session_ids.len())?;
+
+            Ok(session_ids.into_iter().map(|session_id| self.verify_session(session_id, client_id)).collect())
+        }
+
+        /// Check if social ID is already bound
+        #[ink(message)]
+        pub fn is_social_id_available(&self, social_id_hash: String) -> bool {
+            !self.social_to_account.contains(&social_id_hash)
+        }
+
+        /// Check if account has registered identity
+        #[ink(message)]
+        pub fn has_identity(&self, account: AccountId) -> bool {
+            self.identities.contains(account)
+        }
+
+        /// Get total number of registered users
+        #[ink(message)]
+        pub fn get_total_users(&self) -> u64 {
+            self.total_users
+        }
+
+        /// Get number of active sessions
+        #[ink(message)]
+        pub fn get_active_sessions(&self) -> u64 {
+            self.active_sessions
+        }
+
+        /// Get contract admin address
+        #[ink(message)]
+        pub fn get_admin(&self) -> AccountId {
+            self.admin
+        }
+
+        /// Get max failed attempts setting
+        #[ink(message)]
+        pub fn get_max_failed_attempts(&self) -> u32 {
+            self.config.max_failed_attempts
+        }
+
+        /// Get lockout duration setting
+        #[ink(message)]
+        pub fn get_lockout_duration(&self) -> u64 {
+            self.config.lockout_duration
+        }
+
+        /// Get all admin-tunable settings
+        #[ink(message)]
+        pub fn get_config(&self) -> Config {
+            self.config
+        }
+
+        /// Get the pending registration commitment for an account
+        #[ink(message)]
+        pub fn get_registration_commitment(&self, account: AccountId) -> Option<RegistrationCommitment> {
+            self.registration_commitments.get(account)
+        }
+
+        /// Get reveal window setting (in blocks)
+        #[ink(message)]
+        pub fn get_reveal_window(&self) -> BlockNumber {
+            self.config.reveal_window
+        }
+
+        /// Check whether single-call registration is enabled
+        #[ink(message)]
+        pub fn is_direct_registration_enabled(&self) -> bool {
+            self.config.direct_registration_enabled
+        }
+
+        /// Get registration deposit setting
+        #[ink(message)]
+        pub fn get_registration_deposit(&self) -> Balance {
+            self.config.registration_deposit
+        }
+
+        /// Get session deposit setting
+        #[ink(message)]
+        pub fn get_session_deposit(&self) -> Balance {
+            self.config.session_deposit
+        }
+
+        /// Get the deposit held for an account's identity
+        #[ink(message)]
+        pub fn get_identity_deposit(&self, account: AccountId) -> Balance {
+            self.identity_deposits.get(account).unwrap_or(0)
+        }
+
+        /// Get the deposit held for a session
+        #[ink(message)]
+        pub fn get_session_deposit_info(&self, session_id: String) -> Option<SessionDeposit> {
+            self.session_deposits.get(&session_id)
+        }
+
+        /// Get total deposits currently held by the contract
+        #[ink(message)]
+        pub fn get_total_deposits(&self) -> Balance {
+            self.total_deposits
+        }
+
+        /// Check whether an operation is paused
+        #[ink(message)]
+        pub fn is_paused(&self, operation: PausableOperation) -> bool {
+            match operation {
+                PausableOperation::Registration => self.pause_flags.registration,
+                PausableOperation::Authentication => self.pause_flags.authentication,
+                PausableOperation::SessionCreation => self.pause_flags.session_creation,
+            }
+        }
+
+        /// Get pause state of all operations
+        #[ink(message)]
+        pub fn get_pause_flags(&self) -> PauseFlags {
+            self.pause_flags.clone()
+        }
+
+        // ========================================
+        // ADMIN FUNCTIONS
+        // ========================================
+
+        /// Transfer admin role to another account
+        #[ink(message)]
+        pub fn transfer_admin(&mut self, new_admin: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            self.env().emit_event(AdminTransferred {
+                previous_admin: self.admin,
+                new_admin,
+            });
+
+            self.admin = new_admin;
+            Ok(())
+        }
+
+        /// Update max failed attempts setting
+        #[ink(message)]
+        pub fn update_max_failed_attempts(&mut self, new_max: u32) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            let config = Config {
+                max_failed_attempts: new_max,
+                ..self.config
+            };
+            config.validate()?;
+
+            self.config_updated(ConfigParameter::MaxFailedAttempts, self.config.max_failed_attempts.into(), new_max.into());
+            self.config = config;
+            Ok(())
+        }
+
+        /// Update lockout duration setting
+        #[ink(message)]
+        pub fn update_lockout_duration(&mut self, new_duration: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            let config = Config {
+                lockout_duration: new_duration,
+                ..self.config
+            };
+            config.validate()?;
+
+            self.config_updated(ConfigParameter::LockoutDuration, self.config.lockout_duration.into(), new_duration.into());
+            self.config = config;
+            Ok(())
+        }
+
+        /// Update reveal window setting (in blocks, within `REVEAL_WINDOW_RANGE`)
+        #[ink(message)]
+        pub fn update_reveal_window(&mut self, new_window: BlockNumber) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            let config = Config {
+                reveal_window: new_window,
+                ..self.config
+            };
+            config.validate()?;
+
+            self.config_updated(ConfigParameter::RevealWindow, self.config.reveal_window.into(), new_window.into());
+            self.config = config;
+            Ok(())
+        }
+
+        /// Update two-factor window setting (within `TWO_FACTOR_WINDOW_RANGE`)
+        #[ink(message)]
+        pub fn update_two_factor_window(&mut self, new_window: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            let config = Config {
+                two_factor_window: new_window,
+                ..self.config
+            };
+            config.validate()?;
+
+            self.config_updated(ConfigParameter::TwoFactorWindow, self.config.two_factor_window.into(), new_window.into());
+            self.config = config;
+            Ok(())
+        }
+
+        /// Enable or disable the single-call `register_identity` path
+        #[ink(message)]
+        pub fn set_direct_registration(&mut self, enabled: bool) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            self.config_updated(
+                ConfigParameter::DirectRegistration,
+                self.config.direct_registration_enabled.into(),
+                enabled.into(),
+            );
+            self.config.direct_registration_enabled = enabled;
+            Ok(())
+        }
+
+        /// Add a social provider to the registration allowlist or remove it (admin only)
+        ///
+        /// Removing a provider blocks new registrations with it; existing identities
+        /// and their counts stay.
+        #[ink(message)]
+        pub fn set_provider_allowed(&mut self, provider: String, allowed: bool) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            if provider.is_empty() || provider.len() > MAX_PROVIDER_LEN {
+                return Err(Error::InvalidProvider);
+            }
+
+            match (self.provider_position(&provider), allowed) {
+                (Some(_), true) => return Ok(()),
+                (None, false) => return Err(Error::ProviderNotAllowed),
+                (None, true) => {
+                    if self.allowed_providers.len() >= MAX_PROVIDERS {
+                        return Err(Error::TooManyProviders);
+                    }
+                    self.allowed_providers.push(&provider);
+                }
+                (Some(index), false) => {
+                    // Swap-remove
+                    if let Some(last) = self.allowed_providers.pop() {
+                        if index < self.allowed_providers.len() {
+                            self.allowed_providers.set(index, &last);
+                        }
+                    }
+                }
+            }
+
+            self.env().emit_event(ProviderUpdated {
+                provider,
+                allowed,
+                updated_by: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Update registration deposit setting (at most `MAX_DEPOSIT`)
+        #[ink(message)]
+        pub fn update_registration_deposit(&mut self, new_deposit: Balance) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            let config = Config {
+                registration_deposit: new_deposit,
+                ..self.config
+            };
+            config.validate()?;
+
+            self.config_updated(ConfigParameter::RegistrationDeposit, self.config.registration_deposit, new_deposit);
+            self.config = config;
+            Ok(())
+        }
+
+        /// Update session deposit setting (at most `MAX_DEPOSIT`)
+        #[ink(message)]
+        pub fn update_session_deposit(&mut self, new_deposit: Balance) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            let config = Config {
+                session_deposit: new_deposit,
+                ..self.config
+            };
+            config.validate()?;
+
+            self.config_updated(ConfigParameter::SessionDeposit, self.config.session_deposit, new_deposit);
+            self.config = config;
+            Ok(())
+        }
+
+        /// Pause or unpause an operation (emergency stop)
+        #[ink(message)]
+        pub fn set_paused(&mut self, operation: PausableOperation, paused: bool) -> Result<()> {
+            let caller = self.env().caller();
+
+            if caller != self.admin {
+                return Err(Error::Unauthorized);
+            }
+
+            match operation {
+                PausableOperation::Registration => self.pause_flags.registration = paused,
+                PausableOperation::Authentication => self.pause_flags.authentication = paused,
+                PausableOperation::SessionCreation => self.pause_flags.session_creation = paused,
+            }
+
+            self.env().emit_event(PauseStatusChanged {
+                operation,
+                paused,
+                changed_by: caller,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        // ========================================
+        // ADMIN HELPERS (Private)
+        // ========================================
+
+        /// Emit `ConfigUpdated` on behalf of the caller
+        fn config_updated(&self, parameter: ConfigParameter, old_value: u128, new_value: u128) {
+            self.env().emit_event(ConfigUpdated {
+                parameter,
+                old_value,
+                new_value,
+                updated_by: self.env().caller(),
+            });
+        }
+
+        // ========================================
+        // AUTHENTICATION HELPERS (Private)
+        // ========================================
+
+        /// Append an entry to the account's audit ring buffer
+        fn record_audit(&mut self, account: AccountId, action: AuditAction) {
+            let count = self.audit_counts.get(account).unwrap_or(0);
+
+            self.audit_entries.insert((account, Self::audit_slot(count)), &AuditEntry {
+                action,
+                actor: self.env().caller(),
+                timestamp: self.env().block_timestamp(),
+            });
+            self.audit_counts.insert(account, &count.saturating_add(1));
+        }
+
+        /// Whether the caller is `account` itself or the admin
+        fn acts_for(&self, account: AccountId) -> bool {
+            let caller = self.env().caller();
+            caller == account || caller == self.admin
+        }
+
+        /// Ring buffer slot for the audit entry with the given sequence number
+        fn audit_slot(sequence: u64) -> u32 {
+            (sequence % u64::from(MAX_AUDIT_ENTRIES)) as u32
+        }
+
+        /// Emit `LoginFailed`, count it in `failed_logins` and return the outcome
+        fn login_failed(
+            &mut self,
+            account: Option<AccountId>,
+            username: String,
+            reason: LoginFailureReason,
+            timestamp: u64,
+        ) -> LoginOutcome {
+            self.failed_logins = self.failed_logins.saturating_add(1);
+
+            let remaining_attempts = account
+                .and_then(|account| self.identities.get(account))
+                .filter(|identity| !self.is_lock_active(identity, timestamp))
+                .map(|identity| self.config.max_failed_attempts.saturating_sub(identity.failed_attempts))
+                .unwrap_or(0);
+
+            self.env().emit_event(LoginFailed {
+                account,
+                username,
+                reason,
+                remaining_attempts,
+                timestamp,
+            });
+
+            if let Some(account) = account {
+                self.record_audit(account, AuditAction::LoginFailed(reason));
+            }
+
+            LoginOutcome::Failed { reason, remaining_attempts }
+        }
+
+        /// Whether a lock is still in force; it lapses `lockout_duration` after the
+        /// last failed attempt even before the next login clears the flag
+        fn is_lock_active(&self, identity: &IdentityInfo, timestamp: u64) -> bool {
+            identity.is_locked
+                && !is_expired(timestamp, identity.last_login.saturating_add(self.config.lockout_duration))
+        }
+
+        /// Clear a lock whose lockout period has passed, together with its failed attempts
+        fn clear_lapsed_lock(&self, identity: &mut IdentityInfo, timestamp: u64) {
+            if identity.is_locked && !self.is_lock_active(identity, timestamp) {
+                identity.is_locked = false;
+                identity.failed_attempts = 0;
+            }
+        }
+
+        fn record_provider_registration(&mut self, provider: &String) {
+            let count = self.provider_registrations.get(provider).unwrap_or(0);
+            self.provider_registrations.insert(provider, &count.saturating_add(1));
+        }
+
+        fn allow_default_providers(&mut self) {
+            for provider in DEFAULT_PROVIDERS {
+                self.allowed_providers.push(&String::from(provider));
+            }
+        }
+
+        /// Position of a provider on the allowlist
+        fn provider_position(&self, provider: &String) -> Option<u32> {
+            (0..self.allowed_providers.len())
+                .find(|&index| self.allowed_providers.get(index).as_ref() == Some(provider))
+        }
+
+        fn apply_unlock(&mut self, account: AccountId) -> Result<()> {
+            let mut identity = self.identities.get(account)
+                .ok_or(Error::IdentityNotFound)?;
+
+            identity.is_locked = false;
+            identity.failed_attempts = 0;
+            self.identities.insert(account, &identity);
+
+            self.env().emit_event(AccountUnlocked {
+                account,
+                timestamp: self.env().block_timestamp(),
+            });
+            self.record_audit(account, AuditAction::AccountUnlocked);
+
+            Ok(())
+        }
+
+        /// Open a pending login that waits for `verify_two_factor`
+        fn start_two_factor(&mut self, account: AccountId, timestamp: u64) -> LoginOutcome {
+            let expires_at = timestamp.saturating_add(self.config.two_factor_window);
+            self.pending_logins.insert(account, &PendingLogin {
+                created_at: timestamp,
+                expires_at,
+            });
+
+            self.env().emit_event(TwoFactorChallenge {
+                account,
+                expires_at,
+            });
+
+            LoginOutcome::TwoFactorRequired { account, expires_at }
+        }
+
+        /// Passkey challenge bound to this contract, the account and its nonce
+        fn passkey_challenge(&self, account: AccountId, nonce: u64) -> [u8; 32] {
+            let mut output = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&(self.env().account_id(), account, nonce), &mut output);
+            output
+        }
+
+        /// Count a failed login attempt and lock the account once the limit is hit
+        fn record_failed_attempt(&mut self, account: AccountId, identity: &mut IdentityInfo, timestamp: u64) {
+            identity.failed_attempts = identity.failed_attempts.saturating_add(1);
+            identity.last_login = timestamp;
+
+            // Lock account if max attempts reached
+            if identity.failed_attempts >= self.config.max_failed_attempts {
+                identity.is_locked = true;
+                self.lockouts = self.lockouts.saturating_add(1);
+                self.env().emit_event(AccountLocked {
+                    account,
+                    username: identity.username.clone(),
+                    reason: String::from("Too many failed login attempts"),
+                    timestamp,
+                });
+                self.record_audit(account, AuditAction::AccountLocked);
+            }
+
+            self.identities.insert(account, identity);
+        }
+
+        /// Check a one-time code against the account's anchor and advance the anchor
+        fn check_two_factor_proof(&mut self, account: AccountId, proof: [u8; 32]) -> bool {
+            let Some(anchor) = self.two_factor_anchors.get(account) else {
+                return false;
+            };
+
+            let mut link = proof;
+            for _ in 0..TWO_FACTOR_LOOKAHEAD {
+                link = self.env().hash_bytes::<Blake2x256>(&link);
+                if link == anchor {
+                    self.two_factor_anchors.insert(account, &proof);
+                    return true;
+                }
+            }
+
+            false
+        }
+
+        /// Remove a session key from its owner's list and emit `SessionKeyRevoked`
+        fn drop_session_key(&mut self, owner: AccountId, key: AccountId) {
+            let mut keys = self.session_keys.get(owner).unwrap_or_default();
+            keys.retain(|k| k.key != key);
+            if keys.is_empty() {
+                self.session_keys.remove(owner);
+            } else {
+                self.session_keys.insert(owner, &keys);
+            }
+
+            self.env().emit_event(SessionKeyRevoked {
+                owner,
+                key,
+                timestamp: self.env().block_timestamp(),
+            });
+        }
+
+        fn clear_two_factor(&mut self, account: AccountId, identity: &mut IdentityInfo) {
+            identity.two_factor_enabled = false;
+            self.identities.insert(account, identity);
+            self.two_factor_anchors.remove(account);
+            self.pending_logins.remove(account);
+
+            self.env().emit_event(TwoFactorDisabled {
+                account,
+                timestamp: self.env().block_timestamp(),
+            });
+            self.record_audit(account, AuditAction::TwoFactorDisabled);
+        }
+
+        // ========================================
+        // USERNAME INDEX HELPERS (Private)
+        // ========================================
+
+        fn add_to_prefix_bucket(&mut self, username_lower: &String) {
+            let bucket = prefix_bucket(username_lower);
+            let len = self.prefix_bucket_len.get(&bucket).unwrap_or(0);
+
+            self.prefix_bucket_entries.insert((bucket.clone(), len), username_lower);
+            self.prefix_bucket_position.insert(username_lower, &len);
+            self.prefix_bucket_len.insert(&bucket, &len.saturating_add(1));
+        }
+
+        /// Swap-remove a username from its prefix bucket
+        fn remove_from_prefix_bucket(&mut self, username_lower: &String) {
+            let Some(index) = self.prefix_bucket_position.take(username_lower) else {
+                return;
+            };
+            let bucket = prefix_bucket(username_lower);
+            let last_index = self.prefix_bucket_len.get(&bucket).unwrap_or(1).saturating_sub(1);
+
+            if let Some(last) = self.prefix_bucket_entries.take((bucket.clone(), last_index)) {
+                if index != last_index {
+                    self.prefix_bucket_entries.insert((bucket.clone(), index), &last);
+                    self.prefix_bucket_position.insert(&last, &index);
+                }
+            }
+
+            if last_index == 0 {
+                self.prefix_bucket_len.remove(&bucket);
+            } else {
+                self.prefix_bucket_len.insert(&bucket, &last_index);
+            }
+        }
+
+        // ========================================
+        // VERIFICATION HELPERS (Private)
+        // ========================================
+
+        /// Whether `account` holds a verification that has not expired
+        ///
+        /// The single definition of "verified" behind `PublicIdentity`, the list
+        /// filter and `AuthentifyQuery::is_verified`.
+        fn has_valid_verification(&self, account: AccountId) -> bool {
+            self.get_verification_level(account).is_some()
+        }
+
+        /// Build the public view of an identity
+        fn public_identity(&self, identity: IdentityInfo) -> PublicIdentity {
+            PublicIdentity {
+                is_verified: self.has_valid_verification(identity.wallet_address),
+                is_locked: self.is_lock_active(&identity, self.env().block_timestamp()),
+                username: identity.username,
+                social_provider: identity.social_provider,
+                wallet_address: identity.wallet_address,
+                created_at: identity.created_at,
+                two_factor_enabled: identity.two_factor_enabled,
+                attributes: self.attributes.get(identity.wallet_address).unwrap_or_default(),
+            }
+        }
+
+        /// Record a verification after the caller and expiry were checked
+        fn apply_verification(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            level: VerificationLevel,
+            evidence_hash: [u8; 32],
+            expires_at: Option<u64>,
+        ) -> Result<()> {
+            let timestamp = self.env().block_timestamp();
+
+            if !self.identities.contains(account) {
+                return Err(Error::IdentityNotFound);
+            }
+
+            self.verifications_granted = self.verifications_granted.saturating_add(1);
+            self.verifications.insert(account, &VerificationRecord {
+                level,
+                verified_by: caller,
+                verified_at: timestamp,
+                evidence_hash,
+                expires_at,
+            });
+
+            if let Some(request_id) = self.pending_request_of.get(account) {
+                let status = match self.verification_requests.get(request_id) {
+                    Some(request) if request.level == level => RequestStatus::Approved,
+                    _ => RequestStatus::Cancelled,
+                };
+                self.resolve_request(request_id, status, caller, None)?;
+            }
+
+            self.env().emit_event(IdentityVerified {
+                account,
+                level,
+                verified_by: caller,
+                expires_at,
+                timestamp,
+            });
+
+            Ok(())
+        }
+
+        fn ensure_verifier(&self, caller: AccountId) -> Result<()> {
+            if !self.is_verifier(caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            Ok(())
+        }
+
+        /// Close a pending request and drop it from the pending list
+        fn resolve_request(
+            &mut self,
+            request_id: u64,
+            status: RequestStatus,
+            resolved_by: AccountId,
+            reason: Option<String>,
+        ) -> Result<()> {
+            let timestamp = self.env().block_timestamp();
+            let mut request = self.verification_requests.get(request_id)
+                .ok_or(Error::RequestNotFound)?;
+
+            if request.status != RequestStatus::Pending {
+                return Err(Error::RequestNotPending);
+            }
+
+            request.status = status;
+            request.resolved_by = Some(resolved_by);
+            request.resolved_at = Some(timestamp);
+            request.reason = reason.clone();
+            self.verification_requests.insert(request_id, &request);
+            self.pending_request_of.remove(request.account);
+
+            // Swap-remove from the pending list
+            if let Some(index) = self.pending_request_index.take(request_id) {
+                if let Some(last_id) = self.pending_requests.pop() {
+                    if last_id != request_id {
+                        self.pending_requests.set(index, &last_id);
+                        self.pending_request_index.insert(last_id, &index);
+                    }
+                }
+            }
+
+            self.env().emit_event(VerificationRequestResolved {
+                account: request.account,
+                request_id,
+                status,
+                resolved_by,
+                reason,
+                timestamp,
+            });
+
+            Ok(())
+        }
+
+        // ========================================
+        // DID HELPERS (Private)
+        // ========================================
+
+        fn ensure_did_controller(&self, subject: AccountId, caller: AccountId) -> Result<()> {
+            let controller = self.get_did_controller(subject)
+                .ok_or(Error::IdentityNotFound)?;
+
+            if caller != controller {
+                return Err(Error::Unauthorized);
+            }
+
+            Ok(())
+        }
+
+        fn validate_did_fragment(&self, fragment: &str) -> Result<()> {
+            if fragment.is_empty() || fragment.len() > 32 || fragment == "wallet" {
+                return Err(Error::InvalidDidFragment);
+            }
+
+            if !fragment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                return Err(Error::InvalidDidFragment);
+            }
+
+            Ok(())
+        }
+
+        // ========================================
+        // SESSION HELPERS (Private)
+        // ========================================
+
+        /// Load a session that is active, unexpired and whose client is still enabled
+        /// and consented to
+        fn check_session(&self, session_id: &String) -> Result<SessionInfo> {
+            let session = self.sessions.get(session_id)
+                .ok_or(Error::SessionNotFound)?;
+
+            if !session.is_active {
+                return Err(Error::SessionAlreadyRevoked);
+            }
+
+            if is_expired(self.env().block_timestamp(), session.expires_at) {
+                return Err(Error::SessionExpired);
+            }
+
+            if let Some(client_id) = session.client_id {
+                self.ensure_client_enabled(client_id)?;
+                // A consent granted after the session was issued does not revive it
+                let consent = self.valid_consent(session.account, client_id)
+                    .ok_or(Error::ConsentRequired)?;
+                if consent.granted_at > session.created_at {
+                    return Err(Error::ConsentRequired);
+                }
+            }
+
+            Ok(session)
+        }
+
+        /// Deactivate a session, drop its session keys and refund its deposit
+        fn close_session(&mut self, session_id: String, mut session: SessionInfo) -> Result<()> {
+            session.is_active = false;
+            self.sessions.insert(&session_id, &session);
+            self.active_sessions = self.active_sessions.saturating_sub(1);
+            self.sessions_revoked = self.sessions_revoked.saturating_add(1);
+
+            // Session keys bound to this session go with it
+            for key in self.session_key_links.take(&session_id).unwrap_or_default() {
+                self.drop_session_key(session.account, key);
+            }
+
+            // Refund session deposit to whoever paid it
+            let mut refunded = 0;
+            if let Some(deposit) = self.session_deposits.take(&session_id) {
+                self.refund(deposit.payer, deposit.amount)?;
+                refunded = deposit.amount;
+            }
+
+            self.env().emit_event(SessionRevoked {
+                session_id,
+                refunded,
+                timestamp: self.env().block_timestamp(),
+            });
+            self.record_audit(session.account, AuditAction::SessionRevoked);
+
+            Ok(())
+        }
+
+        /// Unexpired consent of `account` for a client application
+        fn valid_consent(&self, account: AccountId, client_id: u64) -> Option<ConsentGrant> {
+            let timestamp = self.env().block_timestamp();
+            self.consents.get(account).unwrap_or_default()
+                .into_iter()
+                .find(|grant| grant.client_id == client_id && !is_expired(timestamp, grant.expires_at))
+        }
+
+        fn ensure_consent(&self, account: AccountId, client_id: u64) -> Result<()> {
+            if !self.has_consent(account, client_id) {
+                return Err(Error::ConsentRequired);
+            }
+
+            Ok(())
+        }
+
+        fn ensure_client_enabled(&self, client_id: u64) -> Result<()> {
+            let client = self.clients.get(client_id)
+                .ok_or(Error::ClientNotFound)?;
+
+            if !client.enabled {
+                return Err(Error::ClientDisabled);
+            }
+
+            Ok(())
+        }
+
+        // ========================================
+        // PAUSE HELPERS (Private)
+        // ========================================
+
+        fn ensure_not_paused(&self, operation: PausableOperation) -> Result<()> {
+            if self.is_paused(operation) {
+                return Err(Error::Paused);
+            }
+
+            Ok(())
+        }
+
+        // ========================================
+        // DEPOSIT HELPERS (Private)
+        // ========================================
+
+        fn refund(&mut self, to: AccountId, amount: Balance) -> Result<()> {
+            self.total_deposits = self.total_deposits.saturating_sub(amount);
+            self.env().transfer(to, amount)
+                .map_err(|_| Error::TransferFailed)
+        }
+
+        // ========================================
+        // VALIDATION HELPERS (Private)
+        // ========================================
+
+        fn validate_username(&self, username: &str) -> Result<()> {
+            if username.is_empty() {
+                return Err(Error::EmptyUsername);
+            }
+
+            if username.len() < 3 {
+                return Err(Error::UsernameTooShort);
+            }
+
+            if username.len() > 32 {
+                return Err(Error::UsernameTooLong);
+            }
+
+            // Check if username contains only alphanumeric and underscore
+            if !username.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(Error::InvalidUsernameFormat);
+            }
+
+            Ok(())
+        }
+
+        fn validate_password_hash(&self, password_hash: &str) -> Result<()> {
+            if password_hash.is_empty() {
+                return Err(Error::EmptyPasswordHash);
+            }
+
+            // Allow any non-empty password hash for flexibility
+            // In production, you might want stricter validation
+            if password_hash.len() < 4 {
+                return Err(Error::EmptyPasswordHash);
+            }
+
+            Ok(())
+        }
+
+        fn validate_scopes(&self, scopes: &[String]) -> Result<()> {
+            if scopes.is_empty()
+                || scopes.len() > MAX_SCOPES
+                || scopes.iter().any(|scope| scope.is_empty() || scope.len() > MAX_SCOPE_LEN)
+            {
+                return Err(Error::InvalidScope);
+            }
+
+            Ok(())
+        }
+
+        fn validate_client(&self, name: &str, redirect_uris: &[String]) -> Result<()> {
+            if name.is_empty() || name.len() > MAX_CLIENT_NAME_LEN {
+                return Err(Error::InvalidClientName);
+            }
+
+            if redirect_uris.is_empty()
+                || redirect_uris.len() > MAX_REDIRECT_URIS
+                || redirect_uris.iter().any(|uri| uri.is_empty() || uri.len() > MAX_REDIRECT_URI_LEN)
+            {
+                return Err(Error::InvalidRedirectUri);
+            }
+
+            Ok(())
+        }
+
+        fn ensure_batch_size(&self, len: usize) -> Result<()> {
+            if len > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+
+            Ok(())
+        }
+
+        fn validate_social_id_hash(&self, social_id_hash: &str) -> Result<()> {
+            if social_id_hash.is_empty() {
+                return Err(Error::EmptySocialIdHash);
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Compute the commitment expected by `reveal_registration`
+    ///
+    /// Run this off-chain: sending the username and salt to a node, even in a
+    /// dry-run, gives them away before the reveal.
+    pub fn compute_registration_commitment(username: &str, salt: [u8; 32], account: AccountId) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        ink::env::hash_encoded::<Blake2x256, _>(&(username, salt, account), &mut output);
+        output
+    }
+
+    /// Compute the commitment `set_attribute` expects for a profile attribute value
+    ///
+    /// Run this off-chain, both to commit and to check a disclosed value against
+    /// `get_attribute`: the value and salt must never reach a node.
+    pub fn compute_attribute_commitment(account: AccountId, key: &str, value: &str, salt: [u8; 32]) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        ink::env::hash_encoded::<Blake2x256, _>(&(account, key, value, salt), &mut output);
+        output
+    }
+
+    /// Compute the hash `set_recovery_codes` expects for a plaintext recovery code
+    ///
+    /// Run this off-chain; the plaintext code only goes on-chain when it is burned.
+    pub fn compute_recovery_code_hash(account: AccountId, salt: [u8; 32], code: &str) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        ink::env::hash_encoded::<Blake2x256, _>(&(account, salt, code), &mut output);
+        output
+    }
+
+    /// Search bucket of a lowercase username or prefix: its first `USERNAME_PREFIX_LEN` characters
+    fn prefix_bucket(username_lower: &str) -> String {
+        username_lower.chars().take(USERNAME_PREFIX_LEN).collect()
+    }
+
+    /// Whether a deadline has passed; `expires_at` itself no longer counts as valid
+    ///
+    /// Every expiry in the contract goes through this: sessions, session keys,
+    /// consents, verifications, credentials, pending logins and lockouts.
+    fn is_expired(now: u64, expires_at: u64) -> bool {
+        now >= expires_at
+    }
+
+    impl AuthentifyQuery for Authentify {
+        #[ink(message)]
+        fn has_identity(&self, account: AccountId) -> bool {
+            self.identities.contains(account)
+        }
+
+        #[ink(message)]
+        fn is_verified(&self, account: AccountId) -> bool {
+            self.has_valid_verification(account)
+        }
+
+        #[ink(message)]
+        fn verify_session(&self, session_id: String, client_id: Option<u64>) -> Option<AccountId> {
+            Authentify::verify_session(self, session_id, client_id).ok()
+        }
+
+        #[ink(message)]
+        fn username_of(&self, account: AccountId) -> Option<String> {
+            self.identities.get(account).map(|identity| identity.username)
+        }
+    }
+
+    // ========================================
+    // UNIT TESTS
+    // ========================================
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::webauthn::test_vectors;
+        use parity_scale_codec::Encode;
+
+        fn create_test_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn set_sender(sender: AccountId) {
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sender);
+        }
+
+        /// Contract with the single-call registration path enabled
+        fn new_contract() -> Authentify {
+            let mut authentify = Authentify::new();
+            authentify.set_direct_registration(true).unwrap();
+            authentify
+        }
+
+        fn set_value(value: Balance) {
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(value);
+        }
+
+        fn set_balance(account: AccountId, balance: Balance) {
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(account, balance);
+        }
+
+        fn balance_of(account: AccountId) -> Balance {
+            ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account).unwrap()
+        }
+
+        fn contract_id() -> AccountId {
+            ink::env::test::callee::<ink::env::DefaultEnvironment>()
+        }
+
+        /// One-time code chain `[seed, H(seed), H(H(seed)), ...]` of `length` links
+        fn code_chain(seed: [u8; 32], length: usize) -> Vec<[u8; 32]> {
+            let mut chain = vec![seed];
+            for _ in 1..length {
+                let mut next = [0u8; 32];
+                ink::env::hash_bytes::<Blake2x256>(chain.last().unwrap(), &mut next);
+                chain.push(next);
+            }
+            chain
+        }
+
+        /// All recorded events of type `E`, oldest first
+        fn decoded_events<E: ink::env::Event + parity_scale_codec::Decode>() -> Vec<E> {
+            ink::env::test::recorded_events()
+                .filter(|event| event.topics.first().map(|t| t.as_slice()) == E::SIGNATURE_TOPIC.as_ref().map(|t| t.as_slice()))
+                .map(|event| E::decode(&mut &event.data[..]).unwrap())
+                .collect()
+        }
+
+        /// Assert that `call` succeeds and emits at least one event
+        fn assert_emits<T: core::fmt::Debug>(name: &str, call: impl FnOnce() -> Result<T>) -> T {
+            let before = ink::env::test::recorded_events().count();
+            let result = call();
+            assert!(result.is_ok(), "{name} failed: {result:?}");
+            assert!(ink::env::test::recorded_events().count() > before, "{name} emitted no event");
+            result.unwrap()
+        }
+
+        fn advance_blocks(count: u32) {
+            for _ in 0..count {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+        }
+
+        #[ink::test]
+        fn test_new_works() {
+            let authentify = Authentify::new();
+            assert_eq!(authentify.get_total_users(), 0);
+            assert_eq!(authentify.get_active_sessions(), 0);
+            assert_eq!(authentify.get_max_failed_attempts(), 5);
+        }
+
+        #[ink::test]
+        fn test_register_identity_works() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from("$2a$10$N9qo8uLOickgx2ZMRZoMyeIjZAgcfl7p92ldGxad68LJZdL17lhWy"),
+                String::from("social_hash_123"),
+                String::from("google"),
+            );
+
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_total_users(), 1);
+            assert!(authentify.has_identity(accounts.alice));
+        }
+
+        #[ink::test]
+        fn test_username_validation() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Empty username
+            let result = authentify.register_identity(
+                String::from(""),
+                String::from("valid_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::EmptyUsername));
+
+            // Too short
+            let result = authentify.register_identity(
+                String::from("ab"),
+                String::from("valid_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::UsernameTooShort));
+
+            // Invalid characters
+            let result = authentify.register_identity(
+                String::from("alice@123"),
+                String::from("valid_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::InvalidUsernameFormat));
+        }
+
+        #[ink::test]
+        fn test_duplicate_username_fails() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // First registration
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_hash_1"),
+                String::from("social1"),
+                String::from("google"),
+            );
+
+            // Try same username with different account
+            set_sender(accounts.bob);
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_hash_2"),
+                String::from("social2"),
+                String::from("github"),
+            );
+
+            assert_eq!(result, Err(Error::UsernameAlreadyTaken));
+        }
+
+        #[ink::test]
+        fn test_authenticate_works() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let password_hash = String::from("correct_password_hash");
+
+            // Register
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                password_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Authenticate with correct password
+            let result = authentify.authenticate(
+                String::from("alice"),
+                password_hash.clone(),
+            );
+            assert_eq!(result, Ok(LoginOutcome::Success(accounts.alice)));
+
+            // Authenticate with wrong password
+            let result = authentify.authenticate(
+                String::from("alice"),
+                String::from("wrong_password_hash"),
+            );
+            assert_eq!(
+                result,
+                Ok(LoginOutcome::Failed {
+                    reason: LoginFailureReason::BadCredential,
+                    remaining_attempts: authentify.get_max_failed_attempts() - 1,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn test_account_lockout() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Register
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("correct_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Try wrong password 5 times
+            for _ in 0..5 {
+                let _ = authentify.authenticate(
+                    String::from("alice"),
+                    String::from("wrong_password_hash"),
+                );
+            }
+
+            // 6th attempt reports the lock
+            let result = authentify.authenticate(
+                String::from("alice"),
+                String::from("wrong_password_hash"),
+            );
+            assert_eq!(result, Ok(LoginOutcome::Failed { reason: LoginFailureReason::Locked, remaining_attempts: 0 }));
+        }
+
+        #[ink::test]
+        fn test_username_case_insensitive() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Register with "Alice"
+            let _ = authentify.register_identity(
+                String::from("Alice"),
+                String::from("valid_hash_1"),
+                String::from("social1"),
+                String::from("google"),
+            );
+
+            // Try to register "alice" (lowercase)
+            set_sender(accounts.bob);
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_hash_2"),
+                String::from("social2"),
+                String::from("github"),
+            );
+
+            assert_eq!(result, Err(Error::UsernameAlreadyTaken));
+        }
+
+        #[ink::test]
+        fn test_change_password() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let old_hash = String::from("old_password_hash");
+            let new_hash = String::from("new_password_hash");
+
+            // Register
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                old_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Change password
+            let result = authentify.change_password(old_hash, new_hash.clone());
+            assert!(result.is_ok());
+
+            // Verify new password works
+            let result = authentify.authenticate(
+                String::from("alice"),
+                new_hash,
+            );
+            assert_eq!(result, Ok(LoginOutcome::Success(accounts.alice)));
+        }
+
+        #[ink::test]
+        fn test_verify_identity() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Register
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Check not verified
+            let identity = authentify.get_identity(accounts.alice).unwrap();
+            assert!(!identity.is_verified);
+
+            // Verify (as admin)
+            let result = authentify.verify_identity(
+                accounts.alice,
+                VerificationLevel::KycLite,
+                [1u8; 32],
+                None,
+            );
+            assert!(result.is_ok());
+
+            // Check now verified
+            let identity = authentify.get_identity(accounts.alice).unwrap();
+            assert!(identity.is_verified);
+
+            let record = authentify.get_verification(accounts.alice).unwrap();
+            assert_eq!(record.level, VerificationLevel::KycLite);
+            assert_eq!(record.verified_by, accounts.alice);
+            assert_eq!(record.evidence_hash, [1u8; 32]);
+            assert!(authentify.has_verification_level(accounts.alice, VerificationLevel::Email));
+            assert!(authentify.has_verification_level(accounts.alice, VerificationLevel::KycLite));
+            assert!(!authentify.has_verification_level(accounts.alice, VerificationLevel::KycFull));
+        }
+
+        #[ink::test]
+        fn test_verification_expiry_and_revocation() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Expiry must be in the future
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            let result = authentify.verify_identity(
+                accounts.alice,
+                VerificationLevel::Email,
+                [0u8; 32],
+                Some(1000),
+            );
+            assert_eq!(result, Err(Error::InvalidExpiry));
+
+            // Non-admin cannot verify or revoke
+            set_sender(accounts.bob);
+            let result = authentify.verify_identity(
+                accounts.alice,
+                VerificationLevel::Email,
+                [0u8; 32],
+                None,
+            );
+            assert_eq!(result, Err(Error::Unauthorized));
+            assert_eq!(authentify.revoke_verification(accounts.alice), Err(Error::Unauthorized));
+
+            set_sender(accounts.alice);
+            let result = authentify.verify_identity(
+                accounts.alice,
+                VerificationLevel::KycFull,
+                [2u8; 32],
+                Some(2000),
+            );
+            assert!(result.is_ok());
+            assert_eq!(
+                authentify.get_verification_level(accounts.alice),
+                Some(VerificationLevel::KycFull)
+            );
+
+            // Lapses at expiry
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2000);
+            assert_eq!(authentify.get_verification_level(accounts.alice), None);
+            assert!(!authentify.has_verification_level(accounts.alice, VerificationLevel::Email));
+
+            assert!(authentify.revoke_verification(accounts.alice).is_ok());
+            assert_eq!(authentify.get_verification(accounts.alice), None);
+            assert!(!authentify.get_identity(accounts.alice).unwrap().is_verified);
+            assert_eq!(authentify.revoke_verification(accounts.alice), Err(Error::NotVerified));
+        }
+
+        #[ink::test]
+        fn test_is_username_available() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            assert!(authentify.is_username_available(String::from("alice")));
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            assert!(!authentify.is_username_available(String::from("alice")));
+            assert!(!authentify.is_username_available(String::from("ALICE"))); // Case insensitive
+        }
+
+        #[ink::test]
+        fn test_social_id_validation() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let social_hash = String::from("social_hash_123");
+
+            // Test social ID availability before registration
+            assert!(authentify.is_social_id_available(social_hash.clone()));
+
+            // Register with social ID
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                social_hash.clone(),
+                String::from("google"),
+            );
+
+            // Social ID should no longer be available
+            assert!(!authentify.is_social_id_available(social_hash.clone()));
+
+            // Try to register another user with same social ID
+            set_sender(accounts.bob);
+            let result = authentify.register_identity(
+                String::from("bob"),
+                String::from("different_password"),
+                social_hash.clone(),
+                String::from("github"),
+            );
+            assert_eq!(result, Err(Error::SocialIdAlreadyBound));
+        }
+
+        #[ink::test]
+        fn test_session_management() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let session_id = String::from("session_123");
+
+            // Register user first
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Create session
+            let result = authentify.create_session(
+                accounts.alice,
+                session_id.clone(),
+                3600000, // 1 hour
+                None,
+            );
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_active_sessions(), 1);
+
+            // Verify session
+            let result = authentify.verify_session(session_id.clone(), None);
+            assert_eq!(result, Ok(accounts.alice));
+
+            // Revoke session
+            let result = authentify.revoke_session(session_id.clone());
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_active_sessions(), 0);
+
+            // Try to verify revoked session
+            let result = authentify.verify_session(session_id.clone(), None);
+            assert_eq!(result, Err(Error::SessionAlreadyRevoked));
+        }
+
+        #[ink::test]
+        fn test_session_expiry() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let session_id = String::from("session_expired");
+
+            // Register user
+            assert!(authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            ).is_ok());
+
+            // Create session with a 1 second duration
+            let result = authentify.create_session(
+                accounts.alice,
+                session_id.clone(),
+                1000,
+                None,
+            );
+            assert!(result.is_ok());
+            assert_eq!(authentify.verify_session(session_id.clone(), None), Ok(accounts.alice));
+
+            // The session lapses at its expiry time
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            assert_eq!(authentify.verify_session(session_id, None), Err(Error::SessionExpired));
+        }
+
+        #[ink::test]
+        fn test_get_account_by_username() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Should return None for non-existent username
+            assert_eq!(authentify.get_account_by_username(String::from("alice")), None);
+
+            // Register user
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Should return correct account
+            assert_eq!(
+                authentify.get_account_by_username(String::from("alice")),
+                Some(accounts.alice)
+            );
+
+            // Should work case-insensitively
+            assert_eq!(
+                authentify.get_account_by_username(String::from("ALICE")),
+                Some(accounts.alice)
+            );
+        }
+
+        #[ink::test]
+        fn test_get_account_by_social() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let social_hash = String::from("google_hash_123");
+
+            // Should return None for non-existent social hash
+            assert_eq!(authentify.get_account_by_social(social_hash.clone()), None);
+
+            // Register user
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                social_hash.clone(),
+                String::from("google"),
+            );
+
+            // Should return correct account
+            assert_eq!(
+                authentify.get_account_by_social(social_hash.clone()),
+                Some(accounts.alice)
+            );
+        }
+
+        #[ink::test]
+        fn test_verify_password() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let password_hash = String::from("correct_password_hash");
+
+            // Register user
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                password_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Verify correct password
+            let result = authentify.verify_password(accounts.alice, password_hash.clone());
+            assert_eq!(result, Ok(true));
+
+            // Verify wrong password
+            let result = authentify.verify_password(accounts.alice, String::from("wrong_hash"));
+            assert_eq!(result, Ok(false));
+
+            // Test with non-existent account
+            let result = authentify.verify_password(accounts.bob, password_hash.clone());
+            assert_eq!(result, Err(Error::IdentityNotFound));
+        }
+
+        #[ink::test]
+        fn test_admin_functions() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Alice is admin by default (constructor caller)
+            assert_eq!(authentify.get_admin(), accounts.alice);
+
+            // Transfer admin to Bob
+            let result = authentify.transfer_admin(accounts.bob);
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_admin(), accounts.bob);
+
+            // Alice should no longer be able to perform admin functions
+            let result = authentify.transfer_admin(accounts.charlie);
+            assert_eq!(result, Err(Error::Unauthorized));
+
+            // Bob should be able to perform admin functions
+            set_sender(accounts.bob);
+            let result = authentify.transfer_admin(accounts.charlie);
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_admin(), accounts.charlie);
+        }
+
+        #[ink::test]
+        fn test_update_settings() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Test updating max failed attempts
+            assert_eq!(authentify.get_max_failed_attempts(), 5);
+            let result = authentify.update_max_failed_attempts(3);
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_max_failed_attempts(), 3);
+
+            // Test updating lockout duration
+            assert_eq!(authentify.get_lockout_duration(), 900000);
+            let result = authentify.update_lockout_duration(600000);
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_lockout_duration(), 600000);
+
+            // Test unauthorized access
+            set_sender(accounts.bob);
+            let result = authentify.update_max_failed_attempts(10);
+            assert_eq!(result, Err(Error::Unauthorized));
+            let result = authentify.update_lockout_duration(1200000);
+            assert_eq!(result, Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_unlock_account() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Register user
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("correct_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Lock account by failing authentication multiple times
+            for _ in 0..5 {
+                let _ = authentify.authenticate(
+                    String::from("alice"),
+                    String::from("wrong_password_hash"),
+                );
+            }
+
+            // Verify account is locked
+            let identity = authentify.get_identity(accounts.alice).unwrap();
+            assert!(identity.is_locked);
+
+            // Admin can unlock the account
+            let result = authentify.unlock_account(accounts.alice);
+            assert!(result.is_ok());
+
+            // Verify account is unlocked
+            let identity = authentify.get_full_identity(accounts.alice).unwrap();
+            assert!(!identity.is_locked);
+            assert_eq!(identity.failed_attempts, 0);
+
+            // Test unauthorized unlock
+            set_sender(accounts.bob);
+            let result = authentify.unlock_account(accounts.alice);
+            assert_eq!(result, Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_password_validation_edge_cases() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Empty password hash
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from(""),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::EmptyPasswordHash));
+
+            // Very short password hash (less than 4 chars)
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from("abc"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::EmptyPasswordHash));
+
+            // Empty social ID hash
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from(""),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::EmptySocialIdHash));
+        }
+
+        #[ink::test]
+        fn test_username_length_validation() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Username too long (more than 32 characters)
+            let long_username = "a".repeat(33);
+            let result = authentify.register_identity(
+                long_username,
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::UsernameTooLong));
+
+            // Valid username at boundary (32 characters)
+            let boundary_username = "a".repeat(32);
+            let result = authentify.register_identity(
+                boundary_username,
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert!(result.is_ok());
+        }
+
+        #[ink::test]
+        fn test_multiple_identity_registrations() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // First registration
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from("password1"),
+                String::from("social1"),
+                String::from("google"),
+            );
+            assert!(result.is_ok());
+
+            // Try to register again with same account
+            let result = authentify.register_identity(
+                String::from("alice2"),
+                String::from("password2"),
+                String::from("social2"),
+                String::from("github"),
+            );
+            assert_eq!(result, Err(Error::IdentityAlreadyExists));
+        }
+
+        #[ink::test]
+        fn test_change_password_edge_cases() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Try to change password without identity
+            let result = authentify.change_password(
+                String::from("old_hash"),
+                String::from("new_hash"),
+            );
+            assert_eq!(result, Err(Error::IdentityNotFound));
+
+            // Register user
+            let old_hash = String::from("old_password_hash");
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                old_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Try to change with wrong old password
+            let result = authentify.change_password(
+                String::from("wrong_old_hash"),
+                String::from("new_hash"),
+            );
+            assert_eq!(result, Err(Error::InvalidCredentials));
+
+            // Try to change to empty new password
+            let result = authentify.change_password(
+                old_hash.clone(),
+                String::from(""),
+            );
+            assert_eq!(result, Err(Error::EmptyPasswordHash));
+        }
+
+        #[ink::test]
+        fn test_get_identity_complete() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Should return None for non-existent identity
+            assert_eq!(authentify.get_identity(accounts.alice), None);
+
+            // Register user
+            let username = String::from("alice");
+            let password_hash = String::from("password_hash");
+            let social_hash = String::from("social_hash");
+            let social_provider = String::from("google");
+
+            let _ = authentify.register_identity(
+                username.clone(),
+                password_hash.clone(),
+                social_hash.clone(),
+                social_provider.clone(),
+            );
+
+            // Get identity and verify all fields
+            let identity = authentify.get_full_identity(accounts.alice).unwrap();
+            assert_eq!(identity.username, username);
+            assert_eq!(identity.password_hash, password_hash);
+            assert_eq!(identity.social_id_hash, social_hash);
+            assert_eq!(identity.social_provider, social_provider);
+            assert_eq!(identity.wallet_address, accounts.alice);
+            assert_eq!(identity.failed_attempts, 0);
+            assert!(!identity.is_locked);
+        }
+
+        #[ink::test]
+        fn test_session_not_found() {
+            let authentify = Authentify::new();
+
+            // Try to verify non-existent session
+            let result = authentify.verify_session(String::from("non_existent_session"), None);
+            assert_eq!(result, Err(Error::SessionNotFound));
+
+            // Try to revoke non-existent session
+            let mut authentify = new_contract();
+            let result = authentify.revoke_session(String::from("non_existent_session"));
+            assert_eq!(result, Err(Error::SessionNotFound));
+        }
+
+        #[ink::test]
+        fn test_constructor_with_config() {
+            let authentify = Authentify::new_with_config(Config {
+                max_failed_attempts: 3,
+                lockout_duration: 600000,
+                reveal_window: 50,
+                two_factor_window: 60000,
+                direct_registration_enabled: true,
+                registration_deposit: 100,
+                session_deposit: 10,
+            }).unwrap();
+            assert_eq!(authentify.get_max_failed_attempts(), 3);
+            assert_eq!(authentify.get_lockout_duration(), 600000);
+            assert_eq!(authentify.get_reveal_window(), 50);
+            assert_eq!(authentify.get_two_factor_window(), 60000);
+            assert!(authentify.is_direct_registration_enabled());
+            assert_eq!(authentify.get_registration_deposit(), 100);
+            assert_eq!(authentify.get_session_deposit(), 10);
+            assert_eq!(authentify.get_total_users(), 0);
+            assert_eq!(authentify.get_active_sessions(), 0);
+        }
+
+        #[ink::test]
+        fn test_direct_registration_disabled_by_default() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = Authentify::new();
+            assert!(!authentify.is_direct_registration_enabled());
+
+            let result = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::DirectRegistrationDisabled));
+
+            // Only admin can enable it
+            set_sender(accounts.bob);
+            assert_eq!(authentify.set_direct_registration(true), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_commit_reveal_registration() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = Authentify::new();
+            let salt = [7u8; 32];
+
+            let commitment = compute_registration_commitment(
+                "alice",
+                salt,
+                accounts.alice,
+            );
+            assert!(authentify.commit_registration(commitment).is_ok());
+
+            // Same block reveal is rejected
+            let result = authentify.reveal_registration(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+                salt,
+            );
+            assert_eq!(result, Err(Error::RevealTooEarly));
+
+            advance_blocks(1);
+
+            // Wrong salt does not match
+            let result = authentify.reveal_registration(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+                [8u8; 32],
+            );
+            assert_eq!(result, Err(Error::CommitmentMismatch));
+
+            let result = authentify.reveal_registration(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+                salt,
+            );
+            assert!(result.is_ok());
+            assert!(authentify.has_identity(accounts.alice));
+            assert_eq!(authentify.get_registration_commitment(accounts.alice), None);
+        }
+
+        #[ink::test]
+        fn test_commitment_bound_to_account() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = Authentify::new();
+            let salt = [1u8; 32];
+
+            // Bob copies Alice's commitment from the mempool
+            let commitment = compute_registration_commitment(
+                "alice",
+                salt,
+                accounts.alice,
+            );
+            set_sender(accounts.bob);
+            assert!(authentify.commit_registration(commitment).is_ok());
+            advance_blocks(1);
+
+            let result = authentify.reveal_registration(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+                salt,
+            );
+            assert_eq!(result, Err(Error::CommitmentMismatch));
+        }
+
+        #[ink::test]
+        fn test_commitment_expiry() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = Authentify::new();
+            assert!(authentify.update_reveal_window(5).is_ok());
+            let salt = [2u8; 32];
+
+            let commitment = compute_registration_commitment(
+                "alice",
+                salt,
+                accounts.alice,
+            );
+            assert!(authentify.commit_registration(commitment).is_ok());
+
+            // Cannot be cleared while still revealable
+            assert_eq!(
+                authentify.clear_expired_commitment(accounts.alice),
+                Err(Error::CommitmentStillActive)
+            );
+
+            advance_blocks(6);
+
+            let result = authentify.reveal_registration(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+                salt,
+            );
+            assert_eq!(result, Err(Error::CommitmentExpired));
+            assert!(authentify.get_registration_commitment(accounts.alice).is_some());
+
+            // Stale commitments can be cleared by anyone
+            set_sender(accounts.bob);
+            assert!(authentify.clear_expired_commitment(accounts.alice).is_ok());
+            assert_eq!(
+                authentify.clear_expired_commitment(accounts.alice),
+                Err(Error::CommitmentNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn test_registration_deposit() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            assert!(authentify.update_registration_deposit(100).is_ok());
+            assert_eq!(authentify.get_registration_deposit(), 100);
+
+            // Not enough value transferred
+            set_sender(accounts.bob);
+            set_value(99);
+            let result = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(result, Err(Error::InsufficientDeposit));
+
+            set_value(100);
+            let result = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert!(result.is_ok());
+            assert_eq!(authentify.get_identity_deposit(accounts.bob), 100);
+            assert_eq!(authentify.get_total_deposits(), 100);
+
+            // Only admin can change deposits
+            assert_eq!(authentify.update_registration_deposit(0), Err(Error::Unauthorized));
+            assert_eq!(authentify.update_session_deposit(0), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_delete_identity_refunds_deposit() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            assert!(authentify.update_registration_deposit(100).is_ok());
+
+            set_sender(accounts.bob);
+            set_value(150);
+            let _ = authentify.register_identity(
+                String::from("Bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(authentify.get_identity_deposit(accounts.bob), 150);
+
+            // Contract holds the transferred value
+            set_balance(contract_id(), 1_000_150);
+            let bob_balance = balance_of(accounts.bob);
+            set_value(0);
+
+            assert!(authentify.delete_identity().is_ok());
+            assert_eq!(balance_of(accounts.bob), bob_balance + 150);
+            assert_eq!(balance_of(contract_id()), 1_000_000);
+            assert_eq!(authentify.get_identity_deposit(accounts.bob), 0);
+            assert_eq!(authentify.get_total_deposits(), 0);
+            assert_eq!(authentify.get_total_users(), 0);
+            assert!(!authentify.has_identity(accounts.bob));
+            assert!(authentify.is_username_available(String::from("bob")));
+            assert!(authentify.is_social_id_available(String::from("social_hash")));
+
+            assert_eq!(authentify.delete_identity(), Err(Error::IdentityNotFound));
+        }
+
+        #[ink::test]
+        fn test_delete_identity_revokes_sessions() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            assert!(authentify.update_session_deposit(10).is_ok());
+
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // The admin opens sessions for bob and pays their deposits
+            set_sender(accounts.alice);
+            set_value(10);
+            assert!(authentify.create_session(accounts.bob, String::from("session_1"), 3600000, None).is_ok());
+            assert!(authentify.create_session(accounts.bob, String::from("session_2"), 3600000, None).is_ok());
+            assert!(authentify.revoke_session(String::from("session_2")).is_ok());
+
+            set_balance(contract_id(), 1_000_010);
+            let alice_balance = balance_of(accounts.alice);
+            set_value(0);
+
+            set_sender(accounts.bob);
+            assert!(authentify.delete_identity().is_ok());
+            assert_eq!(balance_of(accounts.alice), alice_balance + 10);
+            assert_eq!(authentify.get_total_deposits(), 0);
+            assert_eq!(authentify.get_active_sessions(), 0);
+            assert_eq!(
+                authentify.verify_session(String::from("session_1"), None),
+                Err(Error::SessionAlreadyRevoked)
+            );
+        }
+
+        #[ink::test]
+        fn test_session_limit_per_account() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            // Accounts without an identity get no sessions
+            assert_eq!(
+                authentify.create_session(accounts.bob, String::from("session_0"), 3600000, None),
+                Err(Error::IdentityNotFound)
+            );
+
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Third parties cannot fill bob's slots
+            set_sender(accounts.charlie);
+            assert_eq!(
+                authentify.create_session(accounts.bob, String::from("session_0"), 3600000, None),
+                Err(Error::Unauthorized)
+            );
+
+            set_sender(accounts.bob);
+            for i in 0..MAX_SESSIONS_PER_ACCOUNT {
+                let session_id = format!("session_{i}");
+                assert!(authentify.create_session(accounts.bob, session_id, 3600000, None).is_ok());
+            }
+
+            let result = authentify.create_session(accounts.bob, String::from("one_more"), 3600000, None);
+            assert_eq!(result, Err(Error::TooManySessions));
+
+            // Revoking frees a slot
+            assert!(authentify.revoke_session(String::from("session_0")).is_ok());
+            assert!(authentify.create_session(accounts.bob, String::from("one_more"), 3600000, None).is_ok());
+            assert_eq!(
+                authentify.create_session(accounts.bob, String::from("too_many"), 3600000, None),
+                Err(Error::TooManySessions)
+            );
+
+            // So does expiry
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(3600000);
+            for i in 0..MAX_SESSIONS_PER_ACCOUNT {
+                let session_id = format!("later_{i}");
+                assert!(authentify.create_session(accounts.bob, session_id, 3600000, None).is_ok());
+            }
+        }
+
+        #[ink::test]
+        fn test_session_deposit() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            assert!(authentify.update_session_deposit(10).is_ok());
+            assert_eq!(authentify.get_session_deposit(), 10);
+
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            let result = authentify.create_session(accounts.bob, String::from("session_1"), 3600000, None);
+            assert_eq!(result, Err(Error::InsufficientDeposit));
+
+            set_value(10);
+            assert!(authentify.create_session(accounts.bob, String::from("session_1"), 3600000, None).is_ok());
+            assert_eq!(
+                authentify.get_session_deposit_info(String::from("session_1")),
+                Some(SessionDeposit { payer: accounts.bob, amount: 10 })
+            );
+            assert_eq!(authentify.get_total_deposits(), 10);
+
+            // Session IDs cannot be reused while the deposit is held
+            let result = authentify.create_session(accounts.bob, String::from("session_1"), 3600000, None);
+            assert_eq!(result, Err(Error::SessionAlreadyExists));
+
+            set_balance(contract_id(), 1_000_010);
+            let bob_balance = balance_of(accounts.bob);
+            set_value(0);
+
+            assert!(authentify.revoke_session(String::from("session_1")).is_ok());
+            assert_eq!(balance_of(accounts.bob), bob_balance + 10);
+            assert_eq!(authentify.get_session_deposit_info(String::from("session_1")), None);
+            assert_eq!(authentify.get_total_deposits(), 0);
+        }
+
+        #[ink::test]
+        fn test_pause_flags() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                password_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            // Only admin can pause
+            set_sender(accounts.bob);
+            assert_eq!(
+                authentify.set_paused(PausableOperation::Registration, true),
+                Err(Error::Unauthorized)
+            );
+
+            set_sender(accounts.alice);
+            assert!(authentify.set_paused(PausableOperation::Registration, true).is_ok());
+            assert!(authentify.set_paused(PausableOperation::Authentication, true).is_ok());
+            assert!(authentify.set_paused(PausableOperation::SessionCreation, true).is_ok());
+            assert_eq!(decoded_events::<PauseStatusChanged>().len(), 3);
+            assert_eq!(
+                authentify.get_pause_flags(),
+                PauseFlags { registration: true, authentication: true, session_creation: true }
+            );
+
+            set_sender(accounts.bob);
+            let result = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash_bob"),
+                String::from("github"),
+            );
+            assert_eq!(result, Err(Error::Paused));
+            assert_eq!(authentify.commit_registration([0u8; 32]), Err(Error::Paused));
+            assert_eq!(
+                authentify.authenticate(String::from("alice"), password_hash.clone()),
+                Err(Error::Paused)
+            );
+            assert_eq!(
+                authentify.create_session(accounts.alice, String::from("session_1"), 3600000, None),
+                Err(Error::Paused)
+            );
+
+            // Read-only queries keep working
+            assert!(authentify.has_identity(accounts.alice));
+            assert_eq!(authentify.verify_password(accounts.alice, password_hash.clone()), Ok(true));
+
+            // Flags are independent
+            set_sender(accounts.alice);
+            assert!(authentify.set_paused(PausableOperation::Authentication, false).is_ok());
+            assert!(authentify.is_paused(PausableOperation::Registration));
+            assert!(!authentify.is_paused(PausableOperation::Authentication));
+            assert_eq!(authentify.authenticate(String::from("alice"), password_hash), Ok(LoginOutcome::Success(accounts.alice)));
+        }
+
+        #[ink::test]
+        fn test_verification_request_queue() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            for (account, username, social) in [
+                (accounts.bob, "bob", "social_bob"),
+                (accounts.charlie, "charlie", "social_charlie"),
+                (accounts.django, "django", "social_django"),
+            ] {
+                set_sender(account);
+                let _ = authentify.register_identity(
+                    String::from(username),
+                    String::from("valid_password_hash"),
+                    String::from(social),
+                    String::from("google"),
+                );
+                assert!(authentify.request_verification(VerificationLevel::Email, [3u8; 32]).is_ok());
+            }
+
+            // One pending request per account
+            assert_eq!(
+                authentify.request_verification(VerificationLevel::KycFull, [3u8; 32]),
+                Err(Error::RequestAlreadyPending)
+            );
+
+            // Unregistered accounts cannot request
+            set_sender(accounts.eve);
+            assert_eq!(
+                authentify.request_verification(VerificationLevel::Email, [3u8; 32]),
+                Err(Error::IdentityNotFound)
+            );
+
+            assert_eq!(authentify.get_pending_request_count(), 3);
+            assert_eq!(authentify.list_pending_requests(0, 2).len(), 2);
+            assert_eq!(authentify.list_pending_requests(2, 10).len(), 1);
+            assert_eq!(authentify.list_pending_requests(5, 10).len(), 0);
+
+            // Non-verifiers cannot resolve requests
+            assert_eq!(
+                authentify.reject_verification_request(0, String::from("no")),
+                Err(Error::Unauthorized)
+            );
+
+            // Admin grants eve the verifier role
+            set_sender(accounts.alice);
+            assert!(authentify.set_verifier(accounts.eve, true).is_ok());
+            assert!(authentify.is_verifier(accounts.eve));
+
+            set_sender(accounts.eve);
+            assert!(authentify.approve_verification_request(0, None).is_ok());
+            assert_eq!(
+                authentify.get_verification_level(accounts.bob),
+                Some(VerificationLevel::Email)
+            );
+            assert_eq!(authentify.get_verification(accounts.bob).unwrap().verified_by, accounts.eve);
+
+            assert!(authentify.reject_verification_request(1, String::from("Blurry document")).is_ok());
+            let request = authentify.get_verification_request(1).unwrap();
+            assert_eq!(request.status, RequestStatus::Rejected);
+            assert_eq!(request.reason, Some(String::from("Blurry document")));
+            assert_eq!(authentify.get_verification(accounts.charlie), None);
+
+            assert_eq!(
+                authentify.approve_verification_request(1, None),
+                Err(Error::RequestNotPending)
+            );
+            assert_eq!(
+                authentify.approve_verification_request(42, None),
+                Err(Error::RequestNotFound)
+            );
+
+            // Remaining request survives the swap-removes
+            let pending = authentify.list_pending_requests(0, 10);
+            assert_eq!(pending.len(), 1);
+            assert_eq!(pending[0].account, accounts.django);
+
+            // verify_identity at another level cancels the pending request
+            assert!(authentify.verify_identity(
+                accounts.django,
+                VerificationLevel::KycLite,
+                [4u8; 32],
+                None,
+            ).is_ok());
+            assert_eq!(authentify.get_verification_request(2).unwrap().status, RequestStatus::Cancelled);
+            assert_eq!(authentify.get_pending_request_count(), 0);
+            assert_eq!(authentify.get_pending_request_of(accounts.django), None);
+
+            // Charlie can ask again after rejection
+            set_sender(accounts.charlie);
+            assert_eq!(authentify.request_verification(VerificationLevel::Email, [5u8; 32]), Ok(3));
+
+            // verify_identity at the requested level approves it
+            set_sender(accounts.eve);
+            assert!(authentify.verify_identity(
+                accounts.charlie,
+                VerificationLevel::Email,
+                [5u8; 32],
+                None,
+            ).is_ok());
+            assert_eq!(authentify.get_verification_request(3).unwrap().status, RequestStatus::Approved);
+        }
+
+        #[ink::test]
+        fn test_resolve_did() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            assert_eq!(authentify.resolve_did(accounts.alice), None);
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            let did = authentify.get_did(accounts.alice);
+            assert!(did.starts_with("did:authentify:0x"));
+            assert_eq!(did.len(), DID_PREFIX.len() + 2 + 64);
+
+            let document = authentify.resolve_did(accounts.alice).unwrap();
+            assert_eq!(document.id, did);
+            assert_eq!(document.controller, accounts.alice);
+            assert_eq!(document.verification_methods.len(), 1);
+            assert_eq!(document.verification_methods[0].id, String::from("wallet"));
+            let wallet_key: &[u8; 32] = accounts.alice.as_ref();
+            assert_eq!(document.verification_methods[0].public_key, wallet_key.to_vec());
+            assert!(document.services.is_empty());
+
+            // Document is SCALE-encodable for off-chain resolvers
+            let encoded = parity_scale_codec::Encode::encode(&document);
+            let decoded: DidDocument = parity_scale_codec::Decode::decode(&mut &encoded[..]).unwrap();
+            assert_eq!(decoded, document);
+        }
+
+        #[ink::test]
+        fn test_did_keys_and_services() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
                 String::from("google"),
             );
 
-            assert!(result.is_ok());
-            assert_eq!(authentify.get_total_users(), 1);
-            assert!(authentify.has_identity(accounts.alice));
+            assert!(authentify.add_did_key(
+                accounts.alice,
+                String::from("key-1"),
+                KeyType::Ed25519,
+                vec![1u8; 32],
+            ).is_ok());
+            assert_eq!(
+                authentify.add_did_key(accounts.alice, String::from("key-1"), KeyType::Ed25519, vec![2u8; 32]),
+                Err(Error::DidEntryAlreadyExists)
+            );
+            assert_eq!(
+                authentify.add_did_key(accounts.alice, String::from("wallet"), KeyType::Sr25519, vec![2u8; 32]),
+                Err(Error::InvalidDidFragment)
+            );
+            assert_eq!(
+                authentify.add_did_key(accounts.alice, String::from("key-2"), KeyType::Sr25519, vec![]),
+                Err(Error::InvalidDidEntry)
+            );
+            assert_eq!(
+                authentify.add_did_key(
+                    accounts.alice,
+                    String::from("key-2"),
+                    KeyType::Sr25519,
+                    vec![2u8; MAX_DID_PUBLIC_KEY_LEN + 1],
+                ),
+                Err(Error::InvalidDidEntry)
+            );
+            assert_eq!(
+                authentify.add_did_service(
+                    accounts.alice,
+                    String::from("hub"),
+                    String::from("LinkedDomains"),
+                    "a".repeat(MAX_DID_ENDPOINT_LEN + 1),
+                ),
+                Err(Error::InvalidDidEntry)
+            );
+            assert!(authentify.add_did_service(
+                accounts.alice,
+                String::from("hub"),
+                String::from("LinkedDomains"),
+                String::from("https://alice.example"),
+            ).is_ok());
+
+            let document = authentify.resolve_did(accounts.alice).unwrap();
+            assert_eq!(document.verification_methods.len(), 2);
+            assert_eq!(document.verification_methods[1].key_type, KeyType::Ed25519);
+            assert_eq!(document.services[0].endpoint, String::from("https://alice.example"));
+
+            // Only the controller can update the document
+            set_sender(accounts.bob);
+            assert_eq!(
+                authentify.remove_did_key(accounts.alice, String::from("key-1")),
+                Err(Error::Unauthorized)
+            );
+
+            // Hand control to bob
+            set_sender(accounts.alice);
+            assert!(authentify.change_did_controller(accounts.alice, accounts.bob).is_ok());
+            assert_eq!(
+                authentify.remove_did_service(accounts.alice, String::from("hub")),
+                Err(Error::Unauthorized)
+            );
+
+            set_sender(accounts.bob);
+            assert!(authentify.remove_did_key(accounts.alice, String::from("key-1")).is_ok());
+            assert!(authentify.remove_did_service(accounts.alice, String::from("hub")).is_ok());
+            assert_eq!(
+                authentify.remove_did_service(accounts.alice, String::from("hub")),
+                Err(Error::DidEntryNotFound)
+            );
+
+            let document = authentify.resolve_did(accounts.alice).unwrap();
+            assert_eq!(document.controller, accounts.bob);
+            assert_eq!(document.verification_methods.len(), 1);
+            assert!(document.services.is_empty());
+
+            // Unregistered subjects have no DID
+            assert_eq!(
+                authentify.add_did_service(
+                    accounts.charlie,
+                    String::from("hub"),
+                    String::from("LinkedDomains"),
+                    String::from("https://charlie.example"),
+                ),
+                Err(Error::IdentityNotFound)
+            );
         }
 
         #[ink::test]
-        fn test_username_validation() {
+        fn test_credential_lifecycle() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let credential_hash = [9u8; 32];
 
-            // Empty username
-            let result = authentify.register_identity(
-                String::from(""),
-                String::from("valid_hash"),
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
-            assert_eq!(result, Err(Error::EmptyUsername));
 
-            // Too short
-            let result = authentify.register_identity(
-                String::from("ab"),
-                String::from("valid_hash"),
+            // Unregistered issuers cannot anchor credentials
+            set_sender(accounts.charlie);
+            let result = authentify.issue_credential(
+                accounts.bob,
+                credential_hash,
+                String::from("KycCredential"),
+                None,
+            );
+            assert_eq!(result, Err(Error::Unauthorized));
+
+            set_sender(accounts.alice);
+            assert!(authentify.set_issuer(accounts.charlie, true).is_ok());
+            assert!(authentify.is_issuer(accounts.charlie));
+
+            set_sender(accounts.charlie);
+            let result = authentify.issue_credential(
+                accounts.eve,
+                credential_hash,
+                String::from("KycCredential"),
+                None,
+            );
+            assert_eq!(result, Err(Error::IdentityNotFound));
+
+            assert_eq!(authentify.check_credential(credential_hash), CredentialStatus::Unknown);
+            let result = authentify.issue_credential(
+                accounts.bob,
+                credential_hash,
+                String::from("KycCredential"),
+                Some(5000),
+            );
+            assert!(result.is_ok());
+            assert_eq!(authentify.check_credential(credential_hash), CredentialStatus::Valid);
+
+            let result = authentify.issue_credential(
+                accounts.bob,
+                credential_hash,
+                String::from("KycCredential"),
+                None,
+            );
+            assert_eq!(result, Err(Error::CredentialAlreadyExists));
+
+            let credential = authentify.get_credential(credential_hash).unwrap();
+            assert_eq!(credential.issuer, accounts.charlie);
+            assert_eq!(credential.subject, accounts.bob);
+
+            // Only the issuer or admin can revoke
+            set_sender(accounts.bob);
+            assert_eq!(authentify.revoke_credential(credential_hash), Err(Error::Unauthorized));
+
+            set_sender(accounts.charlie);
+            assert!(authentify.revoke_credential(credential_hash).is_ok());
+            assert_eq!(authentify.check_credential(credential_hash), CredentialStatus::Revoked);
+            assert_eq!(
+                authentify.revoke_credential(credential_hash),
+                Err(Error::CredentialAlreadyRevoked)
+            );
+            assert_eq!(authentify.revoke_credential([0u8; 32]), Err(Error::CredentialNotFound));
+        }
+
+        #[ink::test]
+        fn test_credential_expiry() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
-            assert_eq!(result, Err(Error::UsernameTooShort));
+            assert!(authentify.set_issuer(accounts.alice, true).is_ok());
 
-            // Invalid characters
-            let result = authentify.register_identity(
-                String::from("alice@123"),
-                String::from("valid_hash"),
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            assert_eq!(
+                authentify.issue_credential(accounts.alice, [1u8; 32], String::from("Email"), Some(1000)),
+                Err(Error::InvalidExpiry)
+            );
+            assert_eq!(
+                authentify.issue_credential(accounts.alice, [1u8; 32], String::new(), None),
+                Err(Error::EmptyCredentialType)
+            );
+            assert_eq!(
+                authentify.issue_credential(accounts.alice, [1u8; 32], "a".repeat(MAX_CREDENTIAL_TYPE_LEN + 1), None),
+                Err(Error::CredentialTypeTooLong)
+            );
+            assert!(authentify.issue_credential(accounts.alice, [1u8; 32], String::from("Email"), Some(2000)).is_ok());
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2000);
+            assert_eq!(authentify.check_credential([1u8; 32]), CredentialStatus::Expired);
+        }
+
+        #[ink::test]
+        fn test_passkey_registry() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let credential_id = vec![0xAB; 16];
+
+            // Requires an identity
+            let result = authentify.add_passkey(
+                credential_id.clone(),
+                test_vectors::cose_key(),
+                test_vectors::rp_id_hash(),
+                String::from("YubiKey"),
+            );
+            assert_eq!(result, Err(Error::IdentityNotFound));
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
-            assert_eq!(result, Err(Error::InvalidUsernameFormat));
+
+            // Rejects keys that are not COSE ES256
+            let result = authentify.add_passkey(
+                credential_id.clone(),
+                vec![0xa0],
+                test_vectors::rp_id_hash(),
+                String::from("YubiKey"),
+            );
+            assert_eq!(result, Err(Error::InvalidPasskeyKey));
+
+            let result = authentify.add_passkey(
+                vec![],
+                test_vectors::cose_key(),
+                test_vectors::rp_id_hash(),
+                String::from("YubiKey"),
+            );
+            assert_eq!(result, Err(Error::InvalidCredentialId));
+
+            let result = authentify.add_passkey(
+                credential_id.clone(),
+                test_vectors::cose_key(),
+                test_vectors::rp_id_hash(),
+                "a".repeat(MAX_PASSKEY_LABEL_LEN + 1),
+            );
+            assert_eq!(result, Err(Error::PasskeyLabelTooLong));
+
+            assert!(authentify.add_passkey(
+                credential_id.clone(),
+                test_vectors::cose_key(),
+                test_vectors::rp_id_hash(),
+                String::from("YubiKey"),
+            ).is_ok());
+            assert_eq!(authentify.get_passkeys(accounts.alice).len(), 1);
+
+            // Credential IDs are globally unique
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("social_hash_bob"),
+                String::from("google"),
+            );
+            let result = authentify.add_passkey(
+                credential_id.clone(),
+                test_vectors::cose_key(),
+                test_vectors::rp_id_hash(),
+                String::from("Stolen"),
+            );
+            assert_eq!(result, Err(Error::PasskeyAlreadyRegistered));
+
+            // Only the owner can remove it
+            assert_eq!(authentify.remove_passkey(credential_id.clone()), Err(Error::PasskeyNotFound));
+
+            set_sender(accounts.alice);
+            assert!(authentify.remove_passkey(credential_id.clone()).is_ok());
+            assert!(authentify.get_passkeys(accounts.alice).is_empty());
+            // Removing the last passkey frees the storage entry
+            assert!(!authentify.passkeys.contains(accounts.alice));
         }
 
         #[ink::test]
-        fn test_duplicate_username_fails() {
+        fn test_authenticate_with_passkey() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let credential_id = vec![0xCD; 16];
 
-            // First registration
             let _ = authentify.register_identity(
                 String::from("alice"),
-                String::from("valid_hash_1"),
-                String::from("social1"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
                 String::from("google"),
             );
+            assert!(authentify.add_passkey(
+                credential_id.clone(),
+                test_vectors::signing_cose_key(),
+                test_vectors::rp_id_hash(),
+                String::from("iPhone"),
+            ).is_ok());
+
+            let challenge = authentify.get_passkey_challenge(accounts.alice);
+            let client_data_json = test_vectors::client_data_json(&challenge);
+            let auth_data = test_vectors::auth_data(0);
+            let signature = test_vectors::sign_assertion(&auth_data, &client_data_json);
+
+            // Unknown credential
+            let result = authentify.authenticate_with_passkey(
+                vec![0x00],
+                auth_data.clone(),
+                client_data_json.clone(),
+                signature.clone(),
+            );
+            assert_eq!(result, Err(Error::PasskeyNotFound));
+
+            // Signature does not match the authenticator data
+            let result = authentify.authenticate_with_passkey(
+                credential_id.clone(),
+                test_vectors::auth_data(1),
+                client_data_json.clone(),
+                signature.clone(),
+            );
+            assert_eq!(result, Err(Error::InvalidSignature));
+
+            // Assertions over another challenge are rejected
+            let other_client_data = test_vectors::client_data_json(&[0u8; 32]);
+            let result = authentify.authenticate_with_passkey(
+                credential_id.clone(),
+                auth_data.clone(),
+                other_client_data.clone(),
+                test_vectors::sign_assertion(&auth_data, &other_client_data),
+            );
+            assert_eq!(result, Err(Error::ChallengeMismatch));
+
+            // Valid assertion; authenticators without a counter report 0
+            set_sender(accounts.django);
+            let result = authentify.authenticate_with_passkey(
+                credential_id.clone(),
+                auth_data.clone(),
+                client_data_json.clone(),
+                signature.clone(),
+            );
+            assert_eq!(result, Ok(LoginOutcome::Success(accounts.alice)));
+
+            // The challenge is consumed, so the assertion cannot be replayed
+            assert_ne!(authentify.get_passkey_challenge(accounts.alice), challenge);
+            let result = authentify.authenticate_with_passkey(
+                credential_id.clone(),
+                auth_data,
+                client_data_json,
+                signature,
+            );
+            assert_eq!(result, Err(Error::ChallengeMismatch));
+
+            // Once an authenticator reports a counter it must keep increasing
+            let client_data_json = test_vectors::client_data_json(&authentify.get_passkey_challenge(accounts.alice));
+            let auth_data = test_vectors::auth_data(2);
+            let result = authentify.authenticate_with_passkey(
+                credential_id.clone(),
+                auth_data.clone(),
+                client_data_json.clone(),
+                test_vectors::sign_assertion(&auth_data, &client_data_json),
+            );
+            assert_eq!(result, Ok(LoginOutcome::Success(accounts.alice)));
+            assert_eq!(authentify.get_passkeys(accounts.alice)[0].sign_count, 2);
+
+            let client_data_json = test_vectors::client_data_json(&authentify.get_passkey_challenge(accounts.alice));
+            let auth_data = test_vectors::auth_data(2);
+            let result = authentify.authenticate_with_passkey(
+                credential_id,
+                auth_data.clone(),
+                client_data_json.clone(),
+                test_vectors::sign_assertion(&auth_data, &client_data_json),
+            );
+            assert_eq!(result, Err(Error::SignCountRegression));
+        }
 
-            // Try same username with different account
-            set_sender(accounts.bob);
-            let result = authentify.register_identity(
+        #[ink::test]
+        fn test_passkey_login_requires_second_factor() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let credential_id = vec![0xCD; 16];
+            let chain = code_chain([5u8; 32], 3);
+
+            let _ = authentify.register_identity(
                 String::from("alice"),
-                String::from("valid_hash_2"),
-                String::from("social2"),
-                String::from("github"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert!(authentify.add_passkey(
+                credential_id.clone(),
+                test_vectors::signing_cose_key(),
+                test_vectors::rp_id_hash(),
+                String::from("iPhone"),
+            ).is_ok());
+            assert!(authentify.enable_two_factor(chain[2]).is_ok());
+
+            let client_data_json = test_vectors::client_data_json(&authentify.get_passkey_challenge(accounts.alice));
+            let auth_data = test_vectors::auth_data(0);
+            let result = authentify.authenticate_with_passkey(
+                credential_id,
+                auth_data.clone(),
+                client_data_json.clone(),
+                test_vectors::sign_assertion(&auth_data, &client_data_json),
+            );
+            let pending = authentify.get_pending_login(accounts.alice).unwrap();
+            assert_eq!(
+                result,
+                Ok(LoginOutcome::TwoFactorRequired { account: accounts.alice, expires_at: pending.expires_at })
+            );
+            assert_eq!(
+                authentify.verify_two_factor(String::from("alice"), chain[1]),
+                Ok(LoginOutcome::Success(accounts.alice))
             );
+        }
 
-            assert_eq!(result, Err(Error::UsernameAlreadyTaken));
+        #[ink::test]
+        fn test_passkey_rp_id_mismatch() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let credential_id = vec![0xEF; 16];
+
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert!(authentify.add_passkey(
+                credential_id.clone(),
+                test_vectors::cose_key(),
+                [0u8; 32],
+                String::from("Other RP"),
+            ).is_ok());
+
+            let result = authentify.authenticate_with_passkey(
+                credential_id,
+                test_vectors::hex(test_vectors::AUTH_DATA_1),
+                test_vectors::CLIENT_DATA_JSON.to_vec(),
+                test_vectors::hex(test_vectors::SIGNATURE_1),
+            );
+            assert_eq!(result, Err(Error::InvalidAuthenticatorData));
         }
 
         #[ink::test]
-        fn test_authenticate_works() {
+        fn test_two_factor_login() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-            let password_hash = String::from("correct_password_hash");
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
+            let chain = code_chain([42u8; 32], 20);
 
-            // Register
             let _ = authentify.register_identity(
                 String::from("alice"),
                 password_hash.clone(),
                 String::from("social_hash"),
                 String::from("google"),
             );
+            assert!(authentify.enable_two_factor(chain[19]).is_ok());
+            assert!(authentify.get_identity(accounts.alice).unwrap().two_factor_enabled);
+            assert_eq!(authentify.enable_two_factor(chain[19]), Err(Error::TwoFactorAlreadyEnabled));
 
-            // Authenticate with correct password
-            let result = authentify.authenticate(
+            // No pending login before the password step
+            assert_eq!(
+                authentify.verify_two_factor(String::from("alice"), chain[18]),
+                Err(Error::NoPendingLogin)
+            );
+
+            // Password alone does not log in
+            let result = authentify.authenticate(String::from("alice"), password_hash.clone());
+            let pending = authentify.get_pending_login(accounts.alice).unwrap();
+            assert_eq!(
+                result,
+                Ok(LoginOutcome::TwoFactorRequired { account: accounts.alice, expires_at: pending.expires_at })
+            );
+            assert_eq!(pending.expires_at, pending.created_at + authentify.get_two_factor_window());
+
+            // Bad code counts as a failed attempt
+            let max_attempts = authentify.get_max_failed_attempts();
+            let result = authentify.verify_two_factor(String::from("alice"), [0u8; 32]);
+            assert_eq!(
+                result,
+                Ok(LoginOutcome::Failed {
+                    reason: LoginFailureReason::BadCredential,
+                    remaining_attempts: max_attempts - 1,
+                })
+            );
+            assert_eq!(authentify.get_full_identity(accounts.alice).unwrap().failed_attempts, 1);
+
+            // Next code (with one skipped link) completes the login
+            let result = authentify.verify_two_factor(String::from("ALICE"), chain[17]);
+            assert_eq!(result, Ok(LoginOutcome::Success(accounts.alice)));
+            assert_eq!(authentify.get_pending_login(accounts.alice), None);
+            assert_eq!(authentify.get_full_identity(accounts.alice).unwrap().failed_attempts, 0);
+
+            // Codes cannot be replayed
+            let _ = authentify.authenticate(String::from("alice"), password_hash.clone());
+            assert!(matches!(
+                authentify.verify_two_factor(String::from("alice"), chain[17]),
+                Ok(LoginOutcome::Failed { reason: LoginFailureReason::BadCredential, .. })
+            ));
+            assert_eq!(
+                authentify.verify_two_factor(String::from("alice"), chain[16]),
+                Ok(LoginOutcome::Success(accounts.alice))
+            );
+
+            // Disabling requires a valid code
+            assert_eq!(authentify.disable_two_factor([0u8; 32]), Err(Error::InvalidTwoFactorCode));
+            assert!(authentify.disable_two_factor(chain[15]).is_ok());
+            assert_eq!(authentify.authenticate(String::from("alice"), password_hash), Ok(LoginOutcome::Success(accounts.alice)));
+            assert_eq!(authentify.disable_two_factor(chain[14]), Err(Error::TwoFactorNotEnabled));
+        }
+
+        #[ink::test]
+        fn test_two_factor_window_expiry() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
+            let chain = code_chain([7u8; 32], 3);
+
+            let _ = authentify.register_identity(
                 String::from("alice"),
                 password_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
             );
-            assert_eq!(result, Ok(accounts.alice));
+            assert!(authentify.update_two_factor_window(*TWO_FACTOR_WINDOW_RANGE.start()).is_ok());
+            assert!(authentify.enable_two_factor(chain[2]).is_ok());
 
-            // Authenticate with wrong password
-            let result = authentify.authenticate(
-                String::from("alice"),
-                String::from("wrong_password_hash"),
+            let _ = authentify.authenticate(String::from("alice"), password_hash);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(*TWO_FACTOR_WINDOW_RANGE.start() + 1);
+
+            assert_eq!(
+                authentify.verify_two_factor(String::from("alice"), chain[1]),
+                Err(Error::TwoFactorExpired)
             );
-            assert_eq!(result, Err(Error::InvalidCredentials));
         }
 
         #[ink::test]
-        fn test_account_lockout() {
+        fn test_two_factor_after_lapsed_lock() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
+            let chain = code_chain([7u8; 32], 3);
+            let max_attempts = authentify.get_max_failed_attempts();
 
-            // Register
             let _ = authentify.register_identity(
                 String::from("alice"),
-                String::from("correct_password_hash"),
+                password_hash.clone(),
                 String::from("social_hash"),
                 String::from("google"),
             );
+            assert!(authentify.update_lockout_duration(*LOCKOUT_DURATION_RANGE.start()).is_ok());
+            assert!(authentify.enable_two_factor(chain[2]).is_ok());
+            let _ = authentify.authenticate(String::from("alice"), password_hash);
 
-            // Try wrong password 5 times
-            for _ in 0..5 {
-                let _ = authentify.authenticate(
-                    String::from("alice"),
-                    String::from("wrong_password_hash"),
-                );
+            // Someone locks the account while the login is pending
+            for _ in 0..max_attempts {
+                let _ = authentify.authenticate(String::from("alice"), String::from("wrong_password"));
             }
+            assert!(authentify.get_identity(accounts.alice).unwrap().is_locked);
 
-            // 6th attempt should return AccountLocked
-            let result = authentify.authenticate(
+            // Once the lock lapses, one bad code does not lock the account again
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(*LOCKOUT_DURATION_RANGE.start());
+            assert_eq!(
+                authentify.verify_two_factor(String::from("alice"), [0u8; 32]),
+                Ok(LoginOutcome::Failed {
+                    reason: LoginFailureReason::BadCredential,
+                    remaining_attempts: max_attempts - 1,
+                })
+            );
+            let identity = authentify.get_full_identity(accounts.alice).unwrap();
+            assert!(!identity.is_locked);
+            assert_eq!(identity.failed_attempts, 1);
+
+            assert_eq!(
+                authentify.verify_two_factor(String::from("alice"), chain[1]),
+                Ok(LoginOutcome::Success(accounts.alice))
+            );
+        }
+
+        #[ink::test]
+        fn test_recovery_codes() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
+            let chain = code_chain([9u8; 32], 3);
+
+            let _ = authentify.register_identity(
                 String::from("alice"),
-                String::from("wrong_password_hash"),
+                password_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+
+            let salt = [11u8; 32];
+            let codes = [String::from("code-one"), String::from("code-two"), String::from("code-three")];
+            let hashes: Vec<[u8; 32]> = codes.iter()
+                .map(|c| compute_recovery_code_hash(accounts.alice, salt, c))
+                .collect();
+            assert!(authentify.set_recovery_codes(salt, hashes.clone(), None).is_ok());
+            assert_eq!(authentify.get_recovery_code_count(accounts.alice), 3);
+            assert_eq!(
+                authentify.set_recovery_codes(salt, vec![[0u8; 32]; MAX_RECOVERY_CODES + 1], None),
+                Err(Error::TooManyRecoveryCodes)
+            );
+
+            // Reset a lost second factor
+            assert_eq!(
+                authentify.consume_recovery_code(codes[0].clone(), RecoveryAction::ResetTwoFactor),
+                Err(Error::TwoFactorNotEnabled)
+            );
+            assert!(authentify.enable_two_factor(chain[2]).is_ok());
+            assert_eq!(
+                authentify.consume_recovery_code(String::from("wrong"), RecoveryAction::ResetTwoFactor),
+                Err(Error::InvalidRecoveryCode)
+            );
+
+            // With 2FA on, the wallet alone cannot swap in its own codes
+            assert_eq!(
+                authentify.set_recovery_codes(salt, vec![[1u8; 32]], None),
+                Err(Error::InvalidTwoFactorCode)
+            );
+            assert_eq!(
+                authentify.set_recovery_codes(salt, vec![[1u8; 32]], Some([0u8; 32])),
+                Err(Error::InvalidTwoFactorCode)
+            );
+            assert!(authentify.set_recovery_codes(salt, hashes, Some(chain[1])).is_ok());
+            assert_eq!(authentify.get_recovery_code_count(accounts.alice), 3);
+
+            assert!(authentify.consume_recovery_code(codes[0].clone(), RecoveryAction::ResetTwoFactor).is_ok());
+            assert!(!authentify.get_identity(accounts.alice).unwrap().two_factor_enabled);
+            assert_eq!(authentify.get_recovery_code_count(accounts.alice), 2);
+
+            // Codes are burned on use
+            assert!(authentify.enable_two_factor(chain[1]).is_ok());
+            assert_eq!(
+                authentify.consume_recovery_code(codes[0].clone(), RecoveryAction::ResetTwoFactor),
+                Err(Error::InvalidRecoveryCode)
+            );
+            assert!(authentify.disable_two_factor(chain[0]).is_ok());
+
+            // Unlock a locked account
+            assert_eq!(
+                authentify.consume_recovery_code(codes[1].clone(), RecoveryAction::UnlockAccount),
+                Err(Error::AccountNotLocked)
+            );
+            for _ in 0..authentify.get_max_failed_attempts() {
+                let _ = authentify.authenticate(String::from("alice"), String::from("wrong_password"));
+            }
+            assert!(authentify.get_identity(accounts.alice).unwrap().is_locked);
+            assert!(authentify.consume_recovery_code(codes[1].clone(), RecoveryAction::UnlockAccount).is_ok());
+            assert!(!authentify.get_identity(accounts.alice).unwrap().is_locked);
+            assert_eq!(authentify.authenticate(String::from("alice"), password_hash), Ok(LoginOutcome::Success(accounts.alice)));
+            assert_eq!(authentify.get_recovery_code_count(accounts.alice), 1);
+
+            // Another account cannot use alice's codes
+            set_sender(accounts.bob);
+            assert_eq!(
+                authentify.consume_recovery_code(codes[2].clone(), RecoveryAction::UnlockAccount),
+                Err(Error::IdentityNotFound)
             );
-            assert_eq!(result, Err(Error::AccountLocked));
         }
 
         #[ink::test]
-        fn test_username_case_insensitive() {
+        fn test_session_keys() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let transfer = String::from("transfer");
 
-            // Register with "Alice"
             let _ = authentify.register_identity(
-                String::from("Alice"),
-                String::from("valid_hash_1"),
-                String::from("social1"),
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
                 String::from("google"),
             );
 
-            // Try to register "alice" (lowercase)
-            set_sender(accounts.bob);
-            let result = authentify.register_identity(
-                String::from("alice"),
-                String::from("valid_hash_2"),
-                String::from("social2"),
-                String::from("github"),
+            assert_eq!(
+                authentify.add_session_key(accounts.bob, vec![], 1000, None),
+                Err(Error::InvalidScope)
+            );
+            assert_eq!(
+                authentify.add_session_key(accounts.bob, vec![transfer.clone()], 0, None),
+                Err(Error::InvalidExpiry)
+            );
+            assert!(authentify.add_session_key(accounts.bob, vec![transfer.clone()], 1000, None).is_ok());
+            assert_eq!(
+                authentify.add_session_key(accounts.bob, vec![transfer.clone()], 1000, None),
+                Err(Error::SessionKeyAlreadyExists)
             );
 
-            assert_eq!(result, Err(Error::UsernameAlreadyTaken));
+            assert!(authentify.is_authorized(accounts.alice, accounts.bob, transfer.clone()));
+            assert!(!authentify.is_authorized(accounts.alice, accounts.bob, String::from("vote")));
+            assert!(!authentify.is_authorized(accounts.alice, accounts.charlie, transfer.clone()));
+            assert!(!authentify.is_authorized(accounts.bob, accounts.bob, transfer.clone()));
+
+            // Keys lapse at expiry
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1001);
+            assert!(!authentify.is_authorized(accounts.alice, accounts.bob, transfer.clone()));
+
+            assert!(authentify.revoke_session_key(accounts.bob).is_ok());
+            assert!(authentify.get_session_keys(accounts.alice).is_empty());
+            assert_eq!(authentify.revoke_session_key(accounts.bob), Err(Error::SessionKeyNotFound));
         }
 
         #[ink::test]
-        fn test_change_password() {
+        fn test_revoke_session_authorization() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-            let old_hash = String::from("old_password_hash");
-            let new_hash = String::from("new_password_hash");
+            let mut authentify = new_contract();
+            let scope = String::from("vote");
+            assert!(authentify.update_session_deposit(10).is_ok());
 
-            // Register
+            set_sender(accounts.bob);
             let _ = authentify.register_identity(
-                String::from("alice"),
-                old_hash.clone(),
+                String::from("bob"),
+                String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
+            set_value(10);
+            assert!(authentify.create_session(accounts.bob, String::from("session_1"), 5000, None).is_ok());
+            set_value(0);
+            assert!(authentify.add_session_key(
+                accounts.django,
+                vec![scope.clone()],
+                1000,
+                Some(String::from("session_1")),
+            ).is_ok());
+
+            // A third party can neither end the session nor its keys
+            set_sender(accounts.charlie);
+            assert_eq!(authentify.revoke_session(String::from("session_1")), Err(Error::Unauthorized));
+            assert_eq!(authentify.verify_session(String::from("session_1"), None), Ok(accounts.bob));
+            assert!(authentify.is_authorized(accounts.bob, accounts.django, scope.clone()));
 
-            // Change password
-            let result = authentify.change_password(old_hash, new_hash.clone());
-            assert!(result.is_ok());
+            set_sender(accounts.bob);
+            set_balance(contract_id(), 1_000_010);
+            assert!(authentify.revoke_session(String::from("session_1")).is_ok());
+            assert!(!authentify.is_authorized(accounts.bob, accounts.django, scope));
 
-            // Verify new password works
-            let result = authentify.authenticate(
-                String::from("alice"),
-                new_hash,
-            );
-            assert_eq!(result, Ok(accounts.alice));
+            // The payer of a client-scoped session may end it
+            set_sender(accounts.charlie);
+            let client_id = authentify.register_client(String::from("App"), vec![String::from("https://app.example")]).unwrap();
+            set_sender(accounts.bob);
+            assert!(authentify.grant_consent(client_id, vec![String::from("profile")], 10000).is_ok());
+            set_sender(accounts.charlie);
+            set_value(10);
+            assert!(authentify.create_session(accounts.bob, String::from("session_2"), 5000, Some(client_id)).is_ok());
+            set_value(0);
+            set_balance(contract_id(), 1_000_010);
+            assert!(authentify.revoke_session(String::from("session_2")).is_ok());
         }
 
         #[ink::test]
-        fn test_verify_identity() {
+        fn test_session_keys_follow_session() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let scope = String::from("vote");
+            let session_id = String::from("session_123");
 
-            // Register
             let _ = authentify.register_identity(
                 String::from("alice"),
                 String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("bob_social_hash"),
+                String::from("google"),
+            );
+            assert!(authentify.create_session(accounts.bob, String::from("bob_session"), 5000, None).is_ok());
+            set_sender(accounts.alice);
+            assert!(authentify.create_session(accounts.alice, session_id.clone(), 5000, None).is_ok());
 
-            // Check not verified
-            let identity = authentify.get_identity(accounts.alice).unwrap();
-            assert!(!identity.is_verified);
+            // Cannot bind to someone else's or a missing session
+            assert_eq!(
+                authentify.add_session_key(accounts.django, vec![scope.clone()], 1000, Some(String::from("bob_session"))),
+                Err(Error::Unauthorized)
+            );
+            assert_eq!(
+                authentify.add_session_key(accounts.django, vec![scope.clone()], 1000, Some(String::from("missing"))),
+                Err(Error::SessionNotFound)
+            );
 
-            // Verify (as admin)
-            let result = authentify.verify_identity(accounts.alice);
-            assert!(result.is_ok());
+            assert!(authentify.add_session_key(accounts.bob, vec![scope.clone()], 1000, Some(session_id.clone())).is_ok());
+            assert!(authentify.add_session_key(accounts.charlie, vec![scope.clone()], 1000, None).is_ok());
+            assert!(authentify.is_authorized(accounts.alice, accounts.bob, scope.clone()));
 
-            // Check now verified
-            let identity = authentify.get_identity(accounts.alice).unwrap();
-            assert!(identity.is_verified);
+            // Revoking the session revokes its keys, unbound keys stay
+            assert!(authentify.revoke_session(session_id).is_ok());
+            assert!(!authentify.is_authorized(accounts.alice, accounts.bob, scope.clone()));
+            assert!(authentify.is_authorized(accounts.alice, accounts.charlie, scope));
+            assert_eq!(authentify.get_session_keys(accounts.alice).len(), 1);
         }
 
         #[ink::test]
-        fn test_is_username_available() {
+        fn test_authentify_query_trait() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let session_id = String::from("session_123");
 
-            assert!(authentify.is_username_available(String::from("alice")));
+            assert!(!AuthentifyQuery::has_identity(&authentify, accounts.alice));
+            assert_eq!(AuthentifyQuery::username_of(&authentify, accounts.alice), None);
 
             let _ = authentify.register_identity(
                 String::from("alice"),
@@ -999,89 +7010,169 @@ mod authentify {
                 String::from("social_hash"),
                 String::from("google"),
             );
-
-            assert!(!authentify.is_username_available(String::from("alice")));
-            assert!(!authentify.is_username_available(String::from("ALICE"))); // Case insensitive
+            assert!(AuthentifyQuery::has_identity(&authentify, accounts.alice));
+            assert_eq!(AuthentifyQuery::username_of(&authentify, accounts.alice), Some(String::from("alice")));
+
+            // Verification counts only until it expires
+            assert!(!AuthentifyQuery::is_verified(&authentify, accounts.alice));
+            assert!(authentify.verify_identity(accounts.alice, VerificationLevel::Email, [1u8; 32], Some(1000)).is_ok());
+            assert!(AuthentifyQuery::is_verified(&authentify, accounts.alice));
+
+            assert!(authentify.create_session(accounts.alice, session_id.clone(), 500, None).is_ok());
+            assert_eq!(AuthentifyQuery::verify_session(&authentify, session_id.clone(), None), Some(accounts.alice));
+            assert_eq!(AuthentifyQuery::verify_session(&authentify, String::from("missing"), None), None);
+
+            // A client-scoped session only opens consumers expecting that client
+            let app = authentify.register_client(String::from("App"), vec![String::from("https://app.example")]).unwrap();
+            assert!(authentify.grant_consent(app, vec![String::from("profile")], 10000).is_ok());
+            assert!(authentify.create_session(accounts.alice, String::from("scoped"), 500, Some(app)).is_ok());
+            assert_eq!(AuthentifyQuery::verify_session(&authentify, String::from("scoped"), None), None);
+            assert_eq!(AuthentifyQuery::verify_session(&authentify, String::from("scoped"), Some(app)), Some(accounts.alice));
+
+            let verified_only = IdentityFilter { verified: Some(true), ..Default::default() };
+            assert!(authentify.get_identity(accounts.alice).unwrap().is_verified);
+            assert_eq!(authentify.list_identities(0, 10, verified_only.clone()).identities.len(), 1);
+
+            // Every view agrees once the verification expires
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            assert!(!AuthentifyQuery::is_verified(&authentify, accounts.alice));
+            assert!(!authentify.get_identity(accounts.alice).unwrap().is_verified);
+            assert!(authentify.list_identities(0, 10, verified_only).identities.is_empty());
+            assert_eq!(AuthentifyQuery::verify_session(&authentify, session_id, None), None);
         }
 
         #[ink::test]
-        fn test_social_id_validation() {
+        fn test_client_registry() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-            let social_hash = String::from("social_hash_123");
+            let mut authentify = new_contract();
+            let uris = vec![String::from("https://app.example/callback")];
 
-            // Test social ID availability before registration
-            assert!(authentify.is_social_id_available(social_hash.clone()));
+            set_sender(accounts.bob);
+            assert_eq!(
+                authentify.register_client(String::new(), uris.clone()),
+                Err(Error::InvalidClientName)
+            );
+            assert_eq!(
+                authentify.register_client(String::from("Example"), vec![]),
+                Err(Error::InvalidRedirectUri)
+            );
+            let client_id = authentify.register_client(String::from("Example"), uris.clone()).unwrap();
+            let client = authentify.get_client(client_id).unwrap();
+            assert_eq!(client.owner, accounts.bob);
+            assert!(client.enabled);
 
-            // Register with social ID
-            let _ = authentify.register_identity(
-                String::from("alice"),
-                String::from("valid_password_hash"),
-                social_hash.clone(),
-                String::from("google"),
+            assert!(authentify.is_redirect_uri_allowed(client_id, uris[0].clone()));
+            assert!(!authentify.is_redirect_uri_allowed(client_id, String::from("https://evil.example")));
+
+            // Only the owner updates
+            let new_uris = vec![String::from("https://app.example/v2")];
+            set_sender(accounts.charlie);
+            assert_eq!(
+                authentify.update_client(client_id, String::from("Renamed"), new_uris.clone()),
+                Err(Error::Unauthorized)
             );
+            set_sender(accounts.bob);
+            assert!(authentify.update_client(client_id, String::from("Renamed"), new_uris.clone()).is_ok());
+            assert_eq!(authentify.get_client(client_id).unwrap().redirect_uris, new_uris);
 
-            // Social ID should no longer be available
-            assert!(!authentify.is_social_id_available(social_hash.clone()));
+            // Owner or admin disables
+            set_sender(accounts.charlie);
+            assert_eq!(authentify.disable_client(client_id), Err(Error::Unauthorized));
+            set_sender(accounts.alice);
+            assert!(authentify.disable_client(client_id).is_ok());
+            assert_eq!(authentify.disable_client(client_id), Err(Error::ClientDisabled));
+            assert!(!authentify.is_redirect_uri_allowed(client_id, new_uris[0].clone()));
+            assert_eq!(authentify.disable_client(99), Err(Error::ClientNotFound));
 
-            // Try to register another user with same social ID
+            // Registrations per owner are capped, disabled clients included
             set_sender(accounts.bob);
-            let result = authentify.register_identity(
-                String::from("bob"),
-                String::from("different_password"),
-                social_hash.clone(),
-                String::from("github"),
+            for _ in 1..MAX_CLIENTS_PER_OWNER {
+                assert!(authentify.register_client(String::from("Example"), uris.clone()).is_ok());
+            }
+            assert_eq!(
+                authentify.register_client(String::from("Example"), uris.clone()),
+                Err(Error::TooManyClients)
             );
-            assert_eq!(result, Err(Error::SocialIdAlreadyBound));
+            set_sender(accounts.charlie);
+            assert!(authentify.register_client(String::from("Example"), uris).is_ok());
         }
 
         #[ink::test]
-        fn test_session_management() {
+        fn test_session_audience() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-            let session_id = String::from("session_123");
+            let mut authentify = new_contract();
+            let uris = vec![String::from("https://app.example/callback")];
+
+            let app = authentify.register_client(String::from("App"), uris.clone()).unwrap();
+            let other = authentify.register_client(String::from("Other"), uris).unwrap();
 
-            // Register user first
             let _ = authentify.register_identity(
                 String::from("alice"),
                 String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
+            assert!(authentify.grant_consent(app, vec![String::from("profile")], 10000).is_ok());
 
-            // Create session
-            let result = authentify.create_session(
-                accounts.alice,
-                session_id.clone(),
-                3600000, // 1 hour
+            assert_eq!(
+                authentify.create_session(accounts.alice, String::from("s0"), 5000, Some(99)),
+                Err(Error::ClientNotFound)
             );
-            assert!(result.is_ok());
-            assert_eq!(authentify.get_active_sessions(), 1);
+            assert!(authentify.create_session(accounts.alice, String::from("s1"), 5000, Some(app)).is_ok());
 
-            // Verify session
-            let result = authentify.verify_session(session_id.clone());
-            assert_eq!(result, Ok(accounts.alice));
+            assert_eq!(authentify.verify_session(String::from("s1"), Some(app)), Ok(accounts.alice));
+            assert_eq!(authentify.verify_session(String::from("s1"), Some(other)), Err(Error::AudienceMismatch));
+            assert_eq!(authentify.verify_session(String::from("s1"), None), Err(Error::AudienceMismatch));
 
-            // Revoke session
-            let result = authentify.revoke_session(session_id.clone());
-            assert!(result.is_ok());
-            assert_eq!(authentify.get_active_sessions(), 0);
+            // Only the account or the client's owner can bind a session to the client
+            set_sender(accounts.charlie);
+            let third = authentify.register_client(String::from("Third"), vec![String::from("https://third.example")]).unwrap();
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                String::from("valid_password_hash"),
+                String::from("bob_social_hash"),
+                String::from("google"),
+            );
+            assert!(authentify.grant_consent(third, vec![String::from("profile")], 10000).is_ok());
+            for caller in [accounts.django, accounts.alice] {
+                set_sender(caller);
+                assert_eq!(
+                    authentify.create_session(accounts.bob, String::from("forged"), 5000, Some(third)),
+                    Err(Error::Unauthorized)
+                );
+            }
+            assert_eq!(authentify.verify_session(String::from("forged"), Some(third)), Err(Error::SessionNotFound));
+            set_sender(accounts.charlie);
+            assert!(authentify.create_session(accounts.bob, String::from("s3"), 5000, Some(third)).is_ok());
+            assert_eq!(authentify.verify_session(String::from("s3"), Some(third)), Ok(accounts.bob));
+            set_sender(accounts.alice);
 
-            // Try to verify revoked session
-            let result = authentify.verify_session(session_id.clone());
-            assert_eq!(result, Err(Error::SessionAlreadyRevoked));
+            // Disabling the client invalidates its sessions and blocks new ones
+            assert!(authentify.disable_client(app).is_ok());
+            assert_eq!(authentify.verify_session(String::from("s1"), Some(app)), Err(Error::ClientDisabled));
+            assert_eq!(
+                authentify.create_session(accounts.alice, String::from("s2"), 5000, Some(app)),
+                Err(Error::ClientDisabled)
+            );
         }
 
         #[ink::test]
-        fn test_session_expiry() {
+        fn test_consent_grants() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-            let session_id = String::from("session_expired");
+            let mut authentify = new_contract();
+            let uris = vec![String::from("https://app.example/callback")];
+            let scopes = vec![String::from("profile"), String::from("email")];
 
-            // Register user
+            let app = authentify.register_client(String::from("App"), uris).unwrap();
+
+            assert_eq!(
+                authentify.grant_consent(app, scopes.clone(), 1000),
+                Err(Error::IdentityNotFound)
+            );
             let _ = authentify.register_identity(
                 String::from("alice"),
                 String::from("valid_password_hash"),
@@ -1089,374 +7180,1124 @@ mod authentify {
                 String::from("google"),
             );
 
-            // Create session with very short duration (1ms)
-            let result = authentify.create_session(
-                accounts.alice,
-                session_id.clone(),
-                1, // 1 millisecond
+            // No consent, no session for the client
+            assert_eq!(
+                authentify.create_session(accounts.alice, String::from("s1"), 5000, Some(app)),
+                Err(Error::ConsentRequired)
             );
-            assert!(result.is_ok());
 
-            // Session should be expired immediately in test environment
-            // Note: In real environment, time advances naturally
-            // For testing purposes, we check the session exists but might be expired
-            let result = authentify.verify_session(session_id.clone());
-            // This might pass or fail depending on timing - in production would be expired
+            assert_eq!(authentify.grant_consent(99, scopes.clone(), 1000), Err(Error::ClientNotFound));
+            assert_eq!(authentify.grant_consent(app, vec![], 1000), Err(Error::InvalidScope));
+            assert_eq!(authentify.grant_consent(app, scopes.clone(), 0), Err(Error::InvalidExpiry));
+            assert!(authentify.grant_consent(app, scopes.clone(), 1000).is_ok());
+            assert!(authentify.has_consent(accounts.alice, app));
+
+            // Granting again replaces the grant
+            assert!(authentify.grant_consent(app, vec![String::from("profile")], 2000).is_ok());
+            let consents = authentify.get_consents(accounts.alice);
+            assert_eq!(consents.len(), 1);
+            assert_eq!(consents[0].scopes, vec![String::from("profile")]);
+            assert_eq!(consents[0].expires_at, 2000);
+
+            assert!(authentify.create_session(accounts.alice, String::from("s1"), 5000, Some(app)).is_ok());
+            assert_eq!(authentify.verify_session(String::from("s1"), Some(app)), Ok(accounts.alice));
+
+            // Revoking consent revokes the client's sessions; granting again does not revive them
+            assert!(authentify.create_session(accounts.alice, String::from("unscoped"), 5000, None).is_ok());
+            assert!(authentify.revoke_consent(app).is_ok());
+            assert!(!authentify.has_consent(accounts.alice, app));
+            assert_eq!(authentify.verify_session(String::from("s1"), Some(app)), Err(Error::SessionAlreadyRevoked));
+            assert_eq!(authentify.verify_session(String::from("unscoped"), None), Ok(accounts.alice));
+            assert_eq!(authentify.revoke_consent(app), Err(Error::ConsentNotFound));
+            assert!(authentify.grant_consent(app, scopes.clone(), 2000).is_ok());
+            assert_eq!(authentify.verify_session(String::from("s1"), Some(app)), Err(Error::SessionAlreadyRevoked));
+
+            // Extending a live grant keeps the sessions issued under it
+            assert!(authentify.create_session(accounts.alice, String::from("s2"), 5000, Some(app)).is_ok());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(500);
+            assert!(authentify.grant_consent(app, scopes.clone(), 3000).is_ok());
+            assert_eq!(authentify.get_consents(accounts.alice)[0].granted_at, 0);
+            assert_eq!(authentify.verify_session(String::from("s2"), Some(app)), Ok(accounts.alice));
+
+            // Consent expires at `expires_at`; a later grant does not revive older sessions
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(3000);
+            assert!(!authentify.has_consent(accounts.alice, app));
+            assert_eq!(
+                authentify.create_session(accounts.alice, String::from("s3"), 5000, Some(app)),
+                Err(Error::ConsentRequired)
+            );
+            assert!(authentify.grant_consent(app, scopes, 4000).is_ok());
+            assert_eq!(authentify.verify_session(String::from("s2"), Some(app)), Err(Error::ConsentRequired));
+            assert!(authentify.create_session(accounts.alice, String::from("s3"), 5000, Some(app)).is_ok());
+            assert_eq!(authentify.verify_session(String::from("s3"), Some(app)), Ok(accounts.alice));
         }
 
         #[ink::test]
-        fn test_get_account_by_username() {
+        fn test_expiry_boundaries() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
 
-            // Should return None for non-existent username
-            assert_eq!(authentify.get_account_by_username(String::from("alice")), None);
-
-            // Register user
             let _ = authentify.register_identity(
                 String::from("alice"),
                 String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
-
-            // Should return correct account
-            assert_eq!(
-                authentify.get_account_by_username(String::from("alice")),
-                Some(accounts.alice)
-            );
-
-            // Should work case-insensitively
+            assert!(authentify.create_session(accounts.alice, String::from("s1"), 1000, None).is_ok());
+            assert!(authentify.add_session_key(accounts.bob, vec![String::from("vote")], 1000, None).is_ok());
+            assert!(authentify.verify_identity(accounts.alice, VerificationLevel::Email, [1u8; 32], Some(1000)).is_ok());
+
+            // Everything is still valid just before `expires_at`...
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(999);
+            assert_eq!(authentify.verify_session(String::from("s1"), None), Ok(accounts.alice));
+            assert!(authentify.is_authorized(accounts.alice, accounts.bob, String::from("vote")));
+            assert!(authentify.get_identity(accounts.alice).unwrap().is_verified);
+
+            // ...and expired at it
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            assert_eq!(authentify.verify_session(String::from("s1"), None), Err(Error::SessionExpired));
+            assert!(!authentify.is_authorized(accounts.alice, accounts.bob, String::from("vote")));
+            assert!(!authentify.get_identity(accounts.alice).unwrap().is_verified);
             assert_eq!(
-                authentify.get_account_by_username(String::from("ALICE")),
-                Some(accounts.alice)
+                authentify.add_session_key(accounts.charlie, vec![String::from("vote")], 1000, None),
+                Err(Error::InvalidExpiry)
             );
         }
 
         #[ink::test]
-        fn test_get_account_by_social() {
+        fn test_profile_attributes() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-            let social_hash = String::from("google_hash_123");
+            let mut authentify = new_contract();
+            let email = String::from("email");
+            let salt = [5u8; 32];
 
-            // Should return None for non-existent social hash
-            assert_eq!(authentify.get_account_by_social(social_hash.clone()), None);
+            let commitment = compute_attribute_commitment(accounts.alice, &email, "alice@example.com", salt);
+            assert_eq!(authentify.set_attribute(email.clone(), commitment), Err(Error::IdentityNotFound));
 
-            // Register user
             let _ = authentify.register_identity(
                 String::from("alice"),
                 String::from("valid_password_hash"),
-                social_hash.clone(),
+                String::from("social_hash"),
                 String::from("google"),
             );
+            assert_eq!(authentify.set_attribute(String::new(), commitment), Err(Error::InvalidAttributeKey));
+            assert!(authentify.set_attribute(email.clone(), commitment).is_ok());
+
+            // The relying party checks a disclosed value against the stored commitment itself
+            let stored = authentify.get_attribute(accounts.alice, email.clone()).unwrap().commitment;
+            assert_eq!(compute_attribute_commitment(accounts.alice, &email, "alice@example.com", salt), stored);
+            assert_ne!(compute_attribute_commitment(accounts.alice, &email, "eve@example.com", salt), stored);
+            assert_ne!(compute_attribute_commitment(accounts.alice, &email, "alice@example.com", [6u8; 32]), stored);
+            assert_ne!(compute_attribute_commitment(accounts.bob, &email, "alice@example.com", salt), stored);
+            assert_eq!(authentify.get_attribute(accounts.bob, email.clone()), None);
+
+            // The public identity lists commitments but no secrets
+            let identity = authentify.get_identity(accounts.alice).unwrap();
+            assert_eq!(identity.username, String::from("alice"));
+            assert_eq!(identity.attributes.len(), 1);
+            assert_eq!(identity.attributes[0].key, email);
 
-            // Should return correct account
-            assert_eq!(
-                authentify.get_account_by_social(social_hash.clone()),
-                Some(accounts.alice)
-            );
+            assert!(authentify.remove_attribute(email.clone()).is_ok());
+            assert_eq!(authentify.get_attribute(accounts.alice, email.clone()), None);
+            assert_eq!(authentify.remove_attribute(email), Err(Error::AttributeNotFound));
         }
 
         #[ink::test]
-        fn test_verify_password() {
+        fn test_public_identity_hides_secrets() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-            let password_hash = String::from("correct_password_hash");
+            let mut authentify = new_contract();
+            let password_hash = String::from("secret_password_hash");
+            let social_hash = String::from("secret_social_hash");
 
-            // Register user
             let _ = authentify.register_identity(
                 String::from("alice"),
                 password_hash.clone(),
-                String::from("social_hash"),
+                social_hash.clone(),
                 String::from("google"),
             );
+            let _ = authentify.authenticate(String::from("alice"), String::from("wrong_password"));
+            assert!(authentify.set_attribute(String::from("email"), [3u8; 32]).is_ok());
 
-            // Verify correct password
-            let result = authentify.verify_password(accounts.alice, password_hash.clone());
-            assert_eq!(result, Ok(true));
+            let leaks = |encoded: Vec<u8>| {
+                [password_hash.as_bytes(), social_hash.as_bytes()]
+                    .iter()
+                    .any(|secret| encoded.windows(secret.len()).any(|window| window == *secret))
+            };
 
-            // Verify wrong password
-            let result = authentify.verify_password(accounts.alice, String::from("wrong_hash"));
-            assert_eq!(result, Ok(false));
+            // Nothing a stranger can query or observe contains a secret
+            set_sender(accounts.bob);
+            let identity = authentify.get_identity(accounts.alice).unwrap();
+            assert_eq!(identity.username, String::from("alice"));
+            assert!(!leaks(identity.encode()));
+            assert!(!leaks(authentify.list_identities(0, 10, IdentityFilter::default()).encode()));
+            assert!(!leaks(authentify.resolve_did(accounts.alice).encode()));
+            assert!(!leaks(authentify.get_account_by_username(String::from("alice")).encode()));
+            assert_eq!(authentify.get_full_identity(accounts.alice), None);
+            for event in ink::env::test::recorded_events() {
+                assert!(!leaks(event.data));
+            }
 
-            // Test with non-existent account
-            let result = authentify.verify_password(accounts.bob, password_hash.clone());
-            assert_eq!(result, Err(Error::IdentityNotFound));
+            // Only the owner gets the full record back
+            set_sender(accounts.alice);
+            let full = authentify.get_full_identity(accounts.alice).unwrap();
+            assert_eq!(full.password_hash, password_hash);
+            assert_eq!(full.social_id_hash, social_hash);
+            assert_eq!(full.failed_attempts, 1);
+            assert_eq!(authentify.get_full_identity(accounts.bob), None);
+
+            // ...or the admin
+            set_sender(accounts.bob);
+            assert!(authentify.register_identity(
+                String::from("bob"),
+                String::from("bob_hash"),
+                String::from("bob_social"),
+                String::from("google"),
+            ).is_ok());
+            set_sender(accounts.charlie);
+            assert_eq!(authentify.get_full_identity(accounts.bob), None);
+            set_sender(accounts.alice);
+            assert_eq!(authentify.get_full_identity(accounts.bob).unwrap().password_hash, String::from("bob_hash"));
         }
 
         #[ink::test]
-        fn test_admin_functions() {
+        fn test_list_identities() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-
-            // Alice is admin by default (constructor caller)
-            assert_eq!(authentify.get_admin(), accounts.alice);
+            let mut authentify = new_contract();
+            let users = [
+                (accounts.alice, "alice", "google"),
+                (accounts.bob, "bob", "github"),
+                (accounts.charlie, "charlie", "google"),
+                (accounts.django, "django", "twitter"),
+            ];
+
+            for (account, username, provider) in users {
+                set_sender(account);
+                let _ = authentify.register_identity(
+                    String::from(username),
+                    String::from("valid_password_hash"),
+                    String::from(username) + "_social",
+                    String::from(provider),
+                );
+            }
 
-            // Transfer admin to Bob
-            let result = authentify.transfer_admin(accounts.bob);
-            assert!(result.is_ok());
-            assert_eq!(authentify.get_admin(), accounts.bob);
+            let page = authentify.list_identities(0, 2, IdentityFilter::default());
+            assert_eq!(page.identities.len(), 2);
+            assert_eq!(page.next_offset, Some(2));
+            let page = authentify.list_identities(2, 10, IdentityFilter::default());
+            assert_eq!(page.identities.len(), 2);
+            assert_eq!(page.next_offset, None);
 
-            // Alice should no longer be able to perform admin functions
-            let result = authentify.transfer_admin(accounts.charlie);
-            assert_eq!(result, Err(Error::Unauthorized));
+            let google = IdentityFilter { provider: Some(String::from("google")), ..Default::default() };
+            let page = authentify.list_identities(0, 10, google);
+            let names: Vec<String> = page.identities.into_iter().map(|i| i.username).collect();
+            assert_eq!(names, vec![String::from("alice"), String::from("charlie")]);
 
-            // Bob should be able to perform admin functions
-            set_sender(accounts.bob);
-            let result = authentify.transfer_admin(accounts.charlie);
-            assert!(result.is_ok());
-            assert_eq!(authentify.get_admin(), accounts.charlie);
+            set_sender(accounts.alice);
+            assert!(authentify.verify_identity(accounts.django, VerificationLevel::Email, [1u8; 32], None).is_ok());
+            let verified = IdentityFilter { verified: Some(true), ..Default::default() };
+            let page = authentify.list_identities(0, 10, verified);
+            assert_eq!(page.identities.len(), 1);
+            assert_eq!(page.identities[0].wallet_address, accounts.django);
+
+            // Deleting swaps the last entry into the freed slot
+            assert!(authentify.delete_identity().is_ok());
+            assert_eq!(authentify.get_identity_count(), 3);
+            let page = authentify.list_identities(0, 10, IdentityFilter::default());
+            let names: Vec<String> = page.identities.into_iter().map(|i| i.username).collect();
+            assert_eq!(names, vec![String::from("django"), String::from("bob"), String::from("charlie")]);
+
+            // Re-registering appends again
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("alice_social"),
+                String::from("google"),
+            );
+            let locked = IdentityFilter { locked: Some(false), provider: Some(String::from("google")), ..Default::default() };
+            let page = authentify.list_identities(0, 10, locked);
+            let names: Vec<String> = page.identities.into_iter().map(|i| i.username).collect();
+            assert_eq!(names, vec![String::from("charlie"), String::from("alice")]);
         }
 
         #[ink::test]
-        fn test_update_settings() {
+        fn test_search_usernames() {
             let accounts = create_test_accounts();
-            set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
-
-            // Test updating max failed attempts
-            assert_eq!(authentify.get_max_failed_attempts(), 5);
-            let result = authentify.update_max_failed_attempts(3);
-            assert!(result.is_ok());
-            assert_eq!(authentify.get_max_failed_attempts(), 3);
+            let mut authentify = new_contract();
+            let users = [
+                (accounts.alice, "Alice"),
+                (accounts.bob, "alicia"),
+                (accounts.charlie, "alfred"),
+                (accounts.django, "ALIBABA"),
+            ];
+
+            for (account, username) in users {
+                set_sender(account);
+                let _ = authentify.register_identity(
+                    String::from(username),
+                    String::from("valid_password_hash"),
+                    String::from(username) + "_social",
+                    String::from("google"),
+                );
+            }
 
-            // Test updating lockout duration
-            assert_eq!(authentify.get_lockout_duration(), 900000);
-            let result = authentify.update_lockout_duration(600000);
-            assert!(result.is_ok());
-            assert_eq!(authentify.get_lockout_duration(), 600000);
+            assert_eq!(authentify.search_usernames(String::from("al"), 10), Err(Error::InvalidPrefix));
 
-            // Test unauthorized access
-            set_sender(accounts.bob);
-            let result = authentify.update_max_failed_attempts(10);
-            assert_eq!(result, Err(Error::Unauthorized));
-            let result = authentify.update_lockout_duration(1200000);
-            assert_eq!(result, Err(Error::Unauthorized));
+            // Case-insensitive, bucketed by the first three characters
+            assert_eq!(
+                authentify.search_usernames(String::from("ALI"), 10).unwrap(),
+                vec![String::from("alice"), String::from("alicia"), String::from("alibaba")]
+            );
+            assert_eq!(
+                authentify.search_usernames(String::from("alic"), 10).unwrap(),
+                vec![String::from("alice"), String::from("alicia")]
+            );
+            assert_eq!(authentify.search_usernames(String::from("alf"), 10).unwrap(), vec![String::from("alfred")]);
+            assert!(authentify.search_usernames(String::from("bob"), 10).unwrap().is_empty());
+
+            // The limit bounds the result
+            assert_eq!(authentify.search_usernames(String::from("ali"), 1).unwrap(), vec![String::from("alice")]);
+
+            // Deleted usernames leave the bucket
+            set_sender(accounts.alice);
+            assert!(authentify.delete_identity().is_ok());
+            assert_eq!(
+                authentify.search_usernames(String::from("ali"), 10).unwrap(),
+                vec![String::from("alibaba"), String::from("alicia")]
+            );
         }
 
         #[ink::test]
-        fn test_unlock_account() {
+        fn test_batch_queries() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
 
-            // Register user
             let _ = authentify.register_identity(
                 String::from("alice"),
-                String::from("correct_password_hash"),
+                String::from("valid_password_hash"),
                 String::from("social_hash"),
                 String::from("google"),
             );
+            assert!(authentify.create_session(accounts.alice, String::from("s1"), 1000, None).is_ok());
+            assert!(authentify.create_session(accounts.alice, String::from("s2"), 1000, None).is_ok());
+            assert!(authentify.revoke_session(String::from("s2")).is_ok());
+
+            let identities = authentify.get_identities(vec![accounts.alice, accounts.bob]).unwrap();
+            assert_eq!(identities.len(), 2);
+            assert_eq!(identities[0].as_ref().unwrap().username, String::from("alice"));
+            assert_eq!(identities[1], None);
+
+            let available = authentify.check_usernames(vec![String::from("ALICE"), String::from("bob")]).unwrap();
+            assert_eq!(available, vec![false, true]);
+
+            let sessions = authentify.verify_sessions(
+                vec![String::from("s1"), String::from("s2"), String::from("s3")],
+                None,
+            ).unwrap();
+            assert_eq!(sessions, vec![
+                Ok(accounts.alice),
+                Err(Error::SessionAlreadyRevoked),
+                Err(Error::SessionNotFound),
+            ]);
+
+            // Hard cap on batch size
+            let too_many = vec![accounts.alice; MAX_BATCH_SIZE + 1];
+            assert_eq!(authentify.get_identities(too_many), Err(Error::BatchTooLarge));
+            assert_eq!(
+                authentify.check_usernames(vec![String::from("alice"); MAX_BATCH_SIZE + 1]),
+                Err(Error::BatchTooLarge)
+            );
+            assert_eq!(
+                authentify.verify_sessions(vec![String::from("s1"); MAX_BATCH_SIZE + 1], None),
+                Err(Error::BatchTooLarge)
+            );
+            assert!(authentify.get_identities(vec![accounts.alice; MAX_BATCH_SIZE]).is_ok());
+        }
 
-            // Lock account by failing authentication multiple times
-            for _ in 0..5 {
-                let _ = authentify.authenticate(
-                    String::from("alice"),
-                    String::from("wrong_password_hash"),
+        #[ink::test]
+        fn test_batch_verify_and_unlock() {
+            let accounts = create_test_accounts();
+            let mut authentify = new_contract();
+
+            for (account, username) in [(accounts.bob, "bob"), (accounts.charlie, "charlie")] {
+                set_sender(account);
+                let _ = authentify.register_identity(
+                    String::from(username),
+                    String::from("valid_password_hash"),
+                    String::from(username) + "_social",
+                    String::from("google"),
                 );
+                for _ in 0..authentify.get_max_failed_attempts() {
+                    let _ = authentify.authenticate(String::from(username), String::from("wrong_password"));
+                }
             }
 
-            // Verify account is locked
-            let identity = authentify.get_identity(accounts.alice).unwrap();
-            assert!(identity.is_locked);
+            let cohort = vec![accounts.bob, accounts.django, accounts.charlie];
 
-            // Admin can unlock the account
-            let result = authentify.unlock_account(accounts.alice);
-            assert!(result.is_ok());
+            // Admin or verifier only
+            assert_eq!(
+                authentify.verify_identities(cohort.clone(), VerificationLevel::Email, [1u8; 32], None),
+                Err(Error::Unauthorized)
+            );
+            assert_eq!(authentify.unlock_accounts(cohort.clone()), Err(Error::Unauthorized));
 
-            // Verify account is unlocked
-            let identity = authentify.get_identity(accounts.alice).unwrap();
-            assert!(!identity.is_locked);
-            assert_eq!(identity.failed_attempts, 0);
+            set_sender(accounts.alice);
+            let events_before = ink::env::test::recorded_events().count();
+            let outcomes = authentify.verify_identities(cohort.clone(), VerificationLevel::Email, [1u8; 32], None).unwrap();
+            assert_eq!(outcomes, vec![Ok(()), Err(Error::IdentityNotFound), Ok(())]);
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 2);
+            assert!(authentify.get_identity(accounts.bob).unwrap().is_verified);
+            assert!(authentify.get_identity(accounts.charlie).unwrap().is_verified);
+
+            let outcomes = authentify.unlock_accounts(cohort).unwrap();
+            assert_eq!(outcomes, vec![Ok(()), Err(Error::IdentityNotFound), Ok(())]);
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 4);
+            assert!(!authentify.get_identity(accounts.bob).unwrap().is_locked);
+            assert!(!authentify.get_identity(accounts.charlie).unwrap().is_locked);
 
-            // Test unauthorized unlock
-            set_sender(accounts.bob);
-            let result = authentify.unlock_account(accounts.alice);
-            assert_eq!(result, Err(Error::Unauthorized));
+            assert_eq!(
+                authentify.unlock_accounts(vec![accounts.bob; MAX_BATCH_SIZE + 1]),
+                Err(Error::BatchTooLarge)
+            );
+            assert_eq!(
+                authentify.verify_identities(vec![accounts.bob; MAX_BATCH_SIZE + 1], VerificationLevel::Email, [1u8; 32], None),
+                Err(Error::BatchTooLarge)
+            );
         }
 
         #[ink::test]
-        fn test_password_validation_edge_cases() {
+        fn test_contract_stats() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+
+            for (account, username, provider) in [
+                (accounts.alice, "alice", "google"),
+                (accounts.bob, "bob", "github"),
+                (accounts.charlie, "charlie", "google"),
+            ] {
+                set_sender(account);
+                let _ = authentify.register_identity(
+                    String::from(username),
+                    String::from("valid_password_hash"),
+                    String::from(username) + "_social",
+                    String::from(provider),
+                );
+            }
 
-            // Empty password hash
-            let result = authentify.register_identity(
-                String::from("alice"),
-                String::from(""),
-                String::from("social_hash"),
-                String::from("google"),
+            set_sender(accounts.alice);
+            let _ = authentify.authenticate(String::from("alice"), String::from("valid_password_hash"));
+            for _ in 0..authentify.get_max_failed_attempts() {
+                let _ = authentify.authenticate(String::from("bob"), String::from("wrong_password"));
+            }
+            assert!(authentify.verify_identity(accounts.alice, VerificationLevel::Email, [1u8; 32], None).is_ok());
+            assert!(authentify.verify_identity(accounts.charlie, VerificationLevel::Email, [1u8; 32], None).is_ok());
+            // Re-verifying counts as another grant
+            assert!(authentify.verify_identity(accounts.charlie, VerificationLevel::KycLite, [1u8; 32], None).is_ok());
+            assert!(authentify.create_session(accounts.alice, String::from("s1"), 1000, None).is_ok());
+            assert!(authentify.create_session(accounts.alice, String::from("s2"), 1000, None).is_ok());
+            assert!(authentify.revoke_session(String::from("s1")).is_ok());
+
+            let stats = authentify.get_stats();
+            assert_eq!(stats.total_users, 3);
+            assert_eq!(stats.active_sessions, 1);
+            assert_eq!(stats.verifications_granted, 3);
+            assert_eq!(stats.lockouts, 1);
+            assert_eq!(stats.total_logins, 1);
+            assert_eq!(stats.failed_logins, authentify.get_max_failed_attempts() as u64);
+            assert_eq!(stats.sessions_created, 2);
+            assert_eq!(stats.sessions_revoked, 1);
+            assert_eq!(
+                stats.registrations_by_provider,
+                vec![
+                    (String::from("google"), 2),
+                    (String::from("github"), 1),
+                    (String::from("twitter"), 0),
+                    (String::from("email"), 0),
+                ]
             );
-            assert_eq!(result, Err(Error::EmptyPasswordHash));
 
-            // Very short password hash (less than 4 chars)
-            let result = authentify.register_identity(
-                String::from("alice"),
-                String::from("abc"),
-                String::from("social_hash"),
-                String::from("google"),
+            // A lock lapses once `lockout_duration` has passed, without another login
+            assert!(authentify.get_identity(accounts.bob).unwrap().is_locked);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(authentify.get_lockout_duration());
+            assert!(!authentify.get_identity(accounts.bob).unwrap().is_locked);
+            let locked_only = IdentityFilter { locked: Some(true), ..Default::default() };
+            assert!(authentify.list_identities(0, 10, locked_only).identities.is_empty());
+
+            // Unlock and delete move the gauges back; grants and lockouts are cumulative
+            assert!(authentify.unlock_account(accounts.bob).is_ok());
+            assert!(authentify.revoke_verification(accounts.alice).is_ok());
+            set_sender(accounts.charlie);
+            assert!(authentify.delete_identity().is_ok());
+
+            let stats = authentify.get_stats();
+            assert_eq!(stats.total_users, 2);
+            assert_eq!(stats.verifications_granted, 3);
+            assert_eq!(stats.lockouts, 1);
+            // Registrations are cumulative
+            assert_eq!(authentify.get_provider_registrations(String::from("google")), 2);
+        }
+
+        #[ink::test]
+        fn test_provider_allowlist() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+
+            assert!(authentify.is_provider_allowed(String::from("google")));
+            assert!(!authentify.is_provider_allowed(String::from("junk")));
+            assert_eq!(
+                authentify.register_identity(
+                    String::from("alice"),
+                    String::from("valid_password_hash"),
+                    String::from("social_hash"),
+                    String::from("junk"),
+                ),
+                Err(Error::ProviderNotAllowed)
             );
-            assert_eq!(result, Err(Error::EmptyPasswordHash));
 
-            // Empty social ID hash
-            let result = authentify.register_identity(
+            // Admin only, bounded names and list length
+            set_sender(accounts.bob);
+            assert_eq!(authentify.set_provider_allowed(String::from("apple"), true), Err(Error::Unauthorized));
+            set_sender(accounts.alice);
+            assert_eq!(authentify.set_provider_allowed(String::new(), true), Err(Error::InvalidProvider));
+            assert_eq!(
+                authentify.set_provider_allowed("p".repeat(MAX_PROVIDER_LEN + 1), true),
+                Err(Error::InvalidProvider)
+            );
+            assert_eq!(authentify.set_provider_allowed(String::from("apple"), false), Err(Error::ProviderNotAllowed));
+            for index in DEFAULT_PROVIDERS.len() as u32..MAX_PROVIDERS {
+                assert!(authentify.set_provider_allowed(format!("provider{index}"), true).is_ok());
+            }
+            assert_eq!(authentify.set_provider_allowed(String::from("apple"), true), Err(Error::TooManyProviders));
+
+            // Removing a provider blocks new registrations but keeps its count
+            assert!(authentify.register_identity(
                 String::from("alice"),
                 String::from("valid_password_hash"),
-                String::from(""),
+                String::from("social_hash"),
                 String::from("google"),
+            ).is_ok());
+            assert!(authentify.set_provider_allowed(String::from("google"), false).is_ok());
+            assert!(!authentify.is_provider_allowed(String::from("google")));
+            assert!(authentify.is_provider_allowed(String::from("email")));
+            assert_eq!(authentify.get_provider_registrations(String::from("google")), 1);
+            assert_eq!(authentify.get_stats().registrations_by_provider.len(), MAX_PROVIDERS as usize - 1);
+            set_sender(accounts.bob);
+            assert_eq!(
+                authentify.register_identity(
+                    String::from("bob"),
+                    String::from("valid_password_hash"),
+                    String::from("social_hash_bob"),
+                    String::from("google"),
+                ),
+                Err(Error::ProviderNotAllowed)
             );
-            assert_eq!(result, Err(Error::EmptySocialIdHash));
         }
 
         #[ink::test]
-        fn test_username_length_validation() {
+        fn test_login_failure_reasons() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
+            let max_attempts = authentify.get_max_failed_attempts();
 
-            // Username too long (more than 32 characters)
-            let long_username = "a".repeat(33);
-            let result = authentify.register_identity(
-                long_username,
-                String::from("valid_password_hash"),
+            let _ = authentify.register_identity(
+                String::from("alice"),
+                password_hash.clone(),
                 String::from("social_hash"),
                 String::from("google"),
             );
-            assert_eq!(result, Err(Error::UsernameTooLong));
 
-            // Valid username at boundary (32 characters)
-            let boundary_username = "a".repeat(32);
-            let result = authentify.register_identity(
-                boundary_username,
-                String::from("valid_password_hash"),
-                String::from("social_hash"),
-                String::from("google"),
+            // Unknown usernames are reported too, without an account
+            let _ = authentify.authenticate(String::from("nobody"), password_hash.clone());
+            let failure = decoded_events::<LoginFailed>().pop().unwrap();
+            assert_eq!(failure.account, None);
+            assert_eq!(failure.reason, LoginFailureReason::UnknownUser);
+            assert_eq!(failure.remaining_attempts, 0);
+
+            let _ = authentify.authenticate(String::from("Alice"), String::from("wrong_password"));
+            let failure = decoded_events::<LoginFailed>().pop().unwrap();
+            assert_eq!(failure.account, Some(accounts.alice));
+            assert_eq!(failure.reason, LoginFailureReason::BadCredential);
+            assert_eq!(failure.remaining_attempts, max_attempts - 1);
+
+            // A paused login is rejected before any failure is recorded
+            assert!(authentify.set_paused(PausableOperation::Authentication, true).is_ok());
+            let failures = decoded_events::<LoginFailed>().len();
+            let failed_logins = authentify.get_stats().failed_logins;
+            assert_eq!(
+                authentify.authenticate(String::from("alice"), password_hash.clone()),
+                Err(Error::Paused)
             );
-            assert!(result.is_ok());
+            assert_eq!(decoded_events::<LoginFailed>().len(), failures);
+            assert_eq!(authentify.get_stats().failed_logins, failed_logins);
+            assert!(authentify.set_paused(PausableOperation::Authentication, false).is_ok());
+
+            // A correct password awaiting its second factor is not a failure
+            assert!(authentify.enable_two_factor([9u8; 32]).is_ok());
+            let failures = decoded_events::<LoginFailed>().len();
+            let challenges = decoded_events::<TwoFactorChallenge>().len();
+            assert!(matches!(
+                authentify.authenticate(String::from("alice"), password_hash.clone()),
+                Ok(LoginOutcome::TwoFactorRequired { .. })
+            ));
+            assert_eq!(decoded_events::<LoginFailed>().len(), failures);
+            assert_eq!(decoded_events::<TwoFactorChallenge>().len(), challenges + 1);
+            assert_eq!(authentify.get_stats().failed_logins, 2);
+            assert!(!matches!(
+                authentify.get_audit_log(accounts.alice, 1)[0].action,
+                AuditAction::LoginFailed(_)
+            ));
+
+            for _ in 1..max_attempts {
+                let _ = authentify.authenticate(String::from("alice"), String::from("wrong_password"));
+            }
+            let _ = authentify.authenticate(String::from("alice"), password_hash);
+            let failure = decoded_events::<LoginFailed>().pop().unwrap();
+            assert_eq!(failure.reason, LoginFailureReason::Locked);
+            assert_eq!(failure.remaining_attempts, 0);
         }
 
         #[ink::test]
-        fn test_multiple_identity_registrations() {
+        fn test_admin_transfer_and_config_events() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+
+            assert!(authentify.update_max_failed_attempts(3).is_ok());
+            let update = decoded_events::<ConfigUpdated>().pop().unwrap();
+            assert_eq!(update.parameter, ConfigParameter::MaxFailedAttempts);
+            assert_eq!(update.old_value, 5);
+            assert_eq!(update.new_value, 3);
+            assert_eq!(update.updated_by, accounts.alice);
+
+            assert!(authentify.update_lockout_duration(600000).is_ok());
+            let update = decoded_events::<ConfigUpdated>().pop().unwrap();
+            assert_eq!(update.parameter, ConfigParameter::LockoutDuration);
+            assert_eq!(update.old_value, 900000);
+            assert_eq!(update.new_value, 600000);
+
+            assert!(authentify.transfer_admin(accounts.bob).is_ok());
+            let transfer = decoded_events::<AdminTransferred>().pop().unwrap();
+            assert_eq!(transfer.previous_admin, accounts.alice);
+            assert_eq!(transfer.new_admin, accounts.bob);
+
+            // Rejected calls emit nothing
+            let before = ink::env::test::recorded_events().count();
+            assert_eq!(authentify.update_lockout_duration(1), Err(Error::Unauthorized));
+            assert_eq!(ink::env::test::recorded_events().count(), before);
+        }
 
-            // First registration
-            let result = authentify.register_identity(
-                String::from("alice"),
-                String::from("password1"),
-                String::from("social1"),
-                String::from("google"),
-            );
-            assert!(result.is_ok());
+        #[ink::test]
+        fn test_constructors_emit_initial_config() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
 
-            // Try to register again with same account
-            let result = authentify.register_identity(
-                String::from("alice2"),
-                String::from("password2"),
-                String::from("social2"),
-                String::from("github"),
-            );
-            assert_eq!(result, Err(Error::IdentityAlreadyExists));
+            let config = Config {
+                max_failed_attempts: 3,
+                lockout_duration: 600000,
+                ..Config::default()
+            };
+            let _authentify = Authentify::new_with_config(config).unwrap();
+            let initialized = decoded_events::<ConfigInitialized>();
+            assert_eq!(initialized.len(), 1);
+            assert_eq!(initialized[0].config, config);
+            assert_eq!(initialized[0].initialized_by, accounts.alice);
+            assert!(decoded_events::<ConfigUpdated>().is_empty());
+
+            let _authentify = Authentify::new();
+            let initialized = decoded_events::<ConfigInitialized>().pop().unwrap();
+            assert_eq!(initialized.config, Config::default());
         }
 
         #[ink::test]
-        fn test_change_password_edge_cases() {
+        fn test_state_changing_admin_messages_emit_events() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+
+            type AdminCall = fn(&mut Authentify) -> Result<()>;
+            let calls: [(&str, AdminCall); 8] = [
+                ("update_max_failed_attempts", |a| a.update_max_failed_attempts(4)),
+                ("update_lockout_duration", |a| a.update_lockout_duration(60000)),
+                ("update_reveal_window", |a| a.update_reveal_window(5)),
+                ("update_two_factor_window", |a| a.update_two_factor_window(60000)),
+                ("set_direct_registration", |a| a.set_direct_registration(false)),
+                ("update_registration_deposit", |a| a.update_registration_deposit(10)),
+                ("update_session_deposit", |a| a.update_session_deposit(10)),
+                ("set_paused", |a| a.set_paused(PausableOperation::Registration, false)),
+            ];
+
+            for (name, call) in calls {
+                let before = ink::env::test::recorded_events().count();
+                assert!(call(&mut authentify).is_ok(), "{name} failed");
+                assert!(ink::env::test::recorded_events().count() > before, "{name} emitted no event");
+            }
 
-            // Try to change password without identity
-            let result = authentify.change_password(
-                String::from("old_hash"),
-                String::from("new_hash"),
+            // Clearing a stale commitment is recorded as well
+            let commitment = compute_registration_commitment(
+                "alice",
+                [2u8; 32],
+                accounts.alice,
             );
-            assert_eq!(result, Err(Error::IdentityNotFound));
+            assert!(authentify.commit_registration(commitment).is_ok());
+            advance_blocks(6);
+            set_sender(accounts.bob);
+            assert!(authentify.clear_expired_commitment(accounts.alice).is_ok());
+            let cleared = decoded_events::<RegistrationCommitmentCleared>().pop().unwrap();
+            assert_eq!(cleared.account, accounts.alice);
+            assert_eq!(cleared.cleared_by, accounts.bob);
+        }
 
-            // Register user
-            let old_hash = String::from("old_password_hash");
-            let _ = authentify.register_identity(
+        #[ink::test]
+        fn test_state_changing_user_messages_emit_events() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
+            let new_password_hash = String::from("new_password_hash");
+            let chain = code_chain([7u8; 32], 4);
+            let scope = String::from("transfer");
+            assert!(authentify.set_direct_registration(true).is_ok());
+
+            // Registration and login
+            assert_emits("register_identity", || authentify.register_identity(
                 String::from("alice"),
-                old_hash.clone(),
+                password_hash.clone(),
                 String::from("social_hash"),
                 String::from("google"),
-            );
+            ));
+            assert_emits("change_password", || authentify.change_password(password_hash.clone(), new_password_hash.clone()));
+            assert_emits("authenticate", || authentify.authenticate(String::from("alice"), new_password_hash.clone()));
+            for _ in 0..DEFAULT_MAX_FAILED_ATTEMPTS {
+                assert_emits("authenticate", || authentify.authenticate(String::from("alice"), password_hash.clone()));
+            }
+            assert_emits("unlock_account", || authentify.unlock_account(accounts.alice));
 
-            // Try to change with wrong old password
-            let result = authentify.change_password(
-                String::from("wrong_old_hash"),
-                String::from("new_hash"),
-            );
-            assert_eq!(result, Err(Error::InvalidCredentials));
+            // Sessions and delegation
+            assert_emits("create_session", || authentify.create_session(accounts.alice, String::from("s1"), 60000, None));
+            assert_emits("add_session_key", || authentify.add_session_key(accounts.bob, vec![scope.clone()], 1000, None));
+            assert_emits("revoke_session_key", || authentify.revoke_session_key(accounts.bob));
+            assert_emits("revoke_session", || authentify.revoke_session(String::from("s1")));
 
-            // Try to change to empty new password
-            let result = authentify.change_password(
-                old_hash.clone(),
-                String::from(""),
-            );
-            assert_eq!(result, Err(Error::EmptyPasswordHash));
+            // Client applications and consent
+            let client_id = assert_emits("register_client", || {
+                authentify.register_client(String::from("App"), vec![String::from("https://app.example")])
+            });
+            assert_emits("update_client", || authentify.update_client(client_id, String::from("App 2"), vec![String::from("https://app.example")]));
+            assert_emits("grant_consent", || authentify.grant_consent(client_id, vec![String::from("profile")], 10000));
+            assert_emits("revoke_consent", || authentify.revoke_consent(client_id));
+            assert_emits("disable_client", || authentify.disable_client(client_id));
+
+            // Profile attributes
+            assert_emits("set_attribute", || authentify.set_attribute(String::from("email"), [5u8; 32]));
+            assert_emits("remove_attribute", || authentify.remove_attribute(String::from("email")));
+
+            // Second factor, recovery codes and passkeys
+            assert_emits("enable_two_factor", || authentify.enable_two_factor(chain[3]));
+            assert_emits("disable_two_factor", || authentify.disable_two_factor(chain[2]));
+            let salt = [11u8; 32];
+            let code = String::from("code-one");
+            let hashes = vec![compute_recovery_code_hash(accounts.alice, salt, &code)];
+            assert_emits("set_recovery_codes", || authentify.set_recovery_codes(salt, hashes, None));
+            assert_emits("enable_two_factor", || authentify.enable_two_factor(chain[1]));
+            assert_emits("consume_recovery_code", || authentify.consume_recovery_code(code, RecoveryAction::ResetTwoFactor));
+            assert_emits("add_passkey", || authentify.add_passkey(
+                vec![0xAB; 16],
+                test_vectors::cose_key(),
+                test_vectors::rp_id_hash(),
+                String::from("YubiKey"),
+            ));
+            assert_emits("remove_passkey", || authentify.remove_passkey(vec![0xAB; 16]));
+
+            // DID document
+            assert_emits("add_did_key", || authentify.add_did_key(accounts.alice, String::from("key-1"), KeyType::Ed25519, vec![1u8; 32]));
+            assert_emits("remove_did_key", || authentify.remove_did_key(accounts.alice, String::from("key-1")));
+            assert_emits("add_did_service", || authentify.add_did_service(
+                accounts.alice,
+                String::from("hub"),
+                String::from("LinkedDomains"),
+                String::from("https://hub.example"),
+            ));
+            assert_emits("remove_did_service", || authentify.remove_did_service(accounts.alice, String::from("hub")));
+
+            // Verification and credentials
+            assert_emits("request_verification", || authentify.request_verification(VerificationLevel::Email, [3u8; 32]));
+            assert_emits("set_verifier", || authentify.set_verifier(accounts.eve, true));
+            assert_emits("set_issuer", || authentify.set_issuer(accounts.charlie, true));
+            set_sender(accounts.eve);
+            assert_emits("approve_verification_request", || authentify.approve_verification_request(0, None));
+            assert_emits("revoke_verification", || authentify.revoke_verification(accounts.alice));
+            set_sender(accounts.charlie);
+            assert_emits("issue_credential", || authentify.issue_credential(accounts.alice, [1u8; 32], String::from("Email"), None));
+            assert_emits("revoke_credential", || authentify.revoke_credential([1u8; 32]));
+
+            // Commit-reveal registration and account removal
+            set_sender(accounts.bob);
+            let salt = [2u8; 32];
+            assert_emits("commit_registration", || authentify.commit_registration(compute_registration_commitment("bob", salt, accounts.bob)));
+            advance_blocks(1);
+            assert_emits("reveal_registration", || authentify.reveal_registration(
+                String::from("bob"),
+                password_hash.clone(),
+                String::from("bob_social_hash"),
+                String::from("google"),
+                salt,
+            ));
+            set_sender(accounts.alice);
+            assert_emits("change_did_controller", || authentify.change_did_controller(accounts.alice, accounts.bob));
+            assert_emits("delete_identity", || authentify.delete_identity());
         }
 
         #[ink::test]
-        fn test_get_identity_complete() {
+        fn test_audit_log() {
             let accounts = create_test_accounts();
             set_sender(accounts.alice);
-            let mut authentify = Authentify::new();
+            let mut authentify = new_contract();
+            let password_hash = String::from("valid_password_hash");
 
-            // Should return None for non-existent identity
-            assert_eq!(authentify.get_identity(accounts.alice), None);
+            set_sender(accounts.bob);
+            let _ = authentify.register_identity(
+                String::from("bob"),
+                password_hash.clone(),
+                String::from("social_hash"),
+                String::from("google"),
+            );
+            assert_eq!(authentify.get_audit_log(accounts.bob, 10), Vec::new());
+
+            let _ = authentify.authenticate(String::from("bob"), String::from("wrong_password"));
+            assert!(authentify.authenticate(String::from("bob"), password_hash.clone()).is_ok());
+            assert!(authentify.change_password(password_hash, String::from("new_password_hash")).is_ok());
+            assert!(authentify.create_session(accounts.bob, String::from("session_1"), 60000, None).is_ok());
+            assert!(authentify.revoke_session(String::from("session_1")).is_ok());
+
+            // Newest first
+            let actions: Vec<AuditAction> = authentify.get_audit_log(accounts.bob, 10)
+                .into_iter()
+                .map(|entry| entry.action)
+                .collect();
+            assert_eq!(actions, vec![
+                AuditAction::SessionRevoked,
+                AuditAction::SessionCreated,
+                AuditAction::PasswordChanged,
+                AuditAction::LoginSucceeded,
+                AuditAction::LoginFailed(LoginFailureReason::BadCredential),
+            ]);
+
+            let latest = authentify.get_audit_log(accounts.bob, 1);
+            assert_eq!(latest.len(), 1);
+            assert_eq!(latest[0].actor, accounts.bob);
+
+            // Third parties cannot open sessions for the account
+            set_sender(accounts.charlie);
+            assert_eq!(
+                authentify.create_session(accounts.bob, String::from("session_2"), 60000, None),
+                Err(Error::Unauthorized)
+            );
 
-            // Register user
-            let username = String::from("alice");
-            let password_hash = String::from("password_hash");
-            let social_hash = String::from("social_hash");
-            let social_provider = String::from("google");
+            // ...nor read its log
+            assert_eq!(authentify.get_audit_log(accounts.bob, 10), Vec::new());
+            set_sender(accounts.bob);
+            assert_eq!(authentify.get_audit_log(accounts.bob, 10).len(), 5);
+
+            // The admin's are recorded
+            set_sender(accounts.alice);
+            assert!(authentify.create_session(accounts.bob, String::from("session_3"), 60000, None).is_ok());
+            let latest = authentify.get_audit_log(accounts.bob, 1);
+            assert_eq!(latest[0].action, AuditAction::SessionCreated);
+            assert_eq!(latest[0].actor, accounts.alice);
+
+            // A lockout is recorded next to the failed attempt that caused it
+            for _ in 0..DEFAULT_MAX_FAILED_ATTEMPTS {
+                let _ = authentify.authenticate(String::from("bob"), String::from("wrong_password"));
+            }
+            let actions: Vec<AuditAction> = authentify.get_audit_log(accounts.bob, 2)
+                .into_iter()
+                .map(|entry| entry.action)
+                .collect();
+            assert_eq!(actions, vec![
+                AuditAction::LoginFailed(LoginFailureReason::BadCredential),
+                AuditAction::AccountLocked,
+            ]);
+        }
 
+        #[ink::test]
+        fn test_audit_log_is_bounded() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
             let _ = authentify.register_identity(
-                username.clone(),
-                password_hash.clone(),
-                social_hash.clone(),
-                social_provider.clone(),
+                String::from("alice"),
+                String::from("valid_password_hash"),
+                String::from("social_hash"),
+                String::from("google"),
             );
 
-            // Get identity and verify all fields
-            let identity = authentify.get_identity(accounts.alice).unwrap();
-            assert_eq!(identity.username, username);
-            assert_eq!(identity.password_hash, password_hash);
-            assert_eq!(identity.social_id_hash, social_hash);
-            assert_eq!(identity.social_provider, social_provider);
-            assert_eq!(identity.wallet_address, accounts.alice);
-            assert!(!identity.is_verified);
-            assert_eq!(identity.failed_attempts, 0);
-            assert!(!identity.is_locked);
+            for i in 0..MAX_AUDIT_ENTRIES {
+                let session_id = format!("session_{i}");
+                assert!(authentify.create_session(accounts.alice, session_id.clone(), 60000, None).is_ok());
+                assert!(authentify.revoke_session(session_id).is_ok());
+            }
+            assert!(authentify.create_session(accounts.alice, String::from("last"), 60000, None).is_ok());
+
+            let log = authentify.get_audit_log(accounts.alice, u32::MAX);
+            assert_eq!(log.len(), MAX_AUDIT_ENTRIES as usize);
+            assert_eq!(log[0].action, AuditAction::SessionCreated);
+            assert_eq!(log[1].action, AuditAction::SessionRevoked);
         }
 
         #[ink::test]
-        fn test_session_not_found() {
+        fn test_config_bounds() {
+            let accounts = create_test_accounts();
+            set_sender(accounts.alice);
+            let mut authentify = new_contract();
+            let default_config = authentify.get_config();
+            let events_before = ink::env::test::recorded_events().count();
+
+            for value in [0, 1, MAX_FAILED_ATTEMPTS_RANGE.end() + 1, u32::MAX] {
+                assert_eq!(authentify.update_max_failed_attempts(value), Err(Error::InvalidMaxFailedAttempts));
+            }
+            for value in [0, 1, LOCKOUT_DURATION_RANGE.end() + 1, u64::MAX] {
+                assert_eq!(authentify.update_lockout_duration(value), Err(Error::InvalidLockoutDuration));
+            }
+            for value in [0, REVEAL_WINDOW_RANGE.end() + 1, BlockNumber::MAX] {
+                assert_eq!(authentify.update_reveal_window(value), Err(Error::InvalidRevealWindow));
+            }
+            for value in [0, 1, TWO_FACTOR_WINDOW_RANGE.end() + 1, u64::MAX] {
+                assert_eq!(authentify.update_two_factor_window(value), Err(Error::InvalidTwoFactorWindow));
+            }
+            for value in [MAX_DEPOSIT + 1, Balance::MAX] {
+                assert_eq!(authentify.update_registration_deposit(value), Err(Error::InvalidDeposit));
+                assert_eq!(authentify.update_session_deposit(value), Err(Error::InvalidDeposit));
+            }
+            assert_eq!(authentify.get_config(), default_config);
+            assert_eq!(ink::env::test::recorded_events().count(), events_before);
+
+            // Bounds are inclusive
+            assert!(authentify.update_max_failed_attempts(*MAX_FAILED_ATTEMPTS_RANGE.start()).is_ok());
+            assert!(authentify.update_lockout_duration(*LOCKOUT_DURATION_RANGE.end()).is_ok());
+            assert!(authentify.update_reveal_window(*REVEAL_WINDOW_RANGE.start()).is_ok());
+            assert!(authentify.update_two_factor_window(*TWO_FACTOR_WINDOW_RANGE.end()).is_ok());
+            assert!(authentify.update_registration_deposit(MAX_DEPOSIT).is_ok());
+            assert!(authentify.update_session_deposit(MAX_DEPOSIT).is_ok());
+            assert_eq!(authentify.get_config(), Config {
+                max_failed_attempts: *MAX_FAILED_ATTEMPTS_RANGE.start(),
+                lockout_duration: *LOCKOUT_DURATION_RANGE.end(),
+                reveal_window: *REVEAL_WINDOW_RANGE.start(),
+                two_factor_window: *TWO_FACTOR_WINDOW_RANGE.end(),
+                direct_registration_enabled: default_config.direct_registration_enabled,
+                registration_deposit: MAX_DEPOSIT,
+                session_deposit: MAX_DEPOSIT,
+            });
+        }
+
+        #[ink::test]
+        fn test_constructor_rejects_invalid_config() {
+            let defaults = Config::default();
+            let invalid = [
+                (Config { max_failed_attempts: 0, ..defaults }, Error::InvalidMaxFailedAttempts),
+                (Config { lockout_duration: 0, ..defaults }, Error::InvalidLockoutDuration),
+                (Config { lockout_duration: u64::MAX, ..defaults }, Error::InvalidLockoutDuration),
+                (Config { reveal_window: 0, ..defaults }, Error::InvalidRevealWindow),
+                (Config { two_factor_window: 0, ..defaults }, Error::InvalidTwoFactorWindow),
+                (Config { registration_deposit: Balance::MAX, ..defaults }, Error::InvalidDeposit),
+                (Config { session_deposit: Balance::MAX, ..defaults }, Error::InvalidDeposit),
+            ];
+            for (config, error) in invalid {
+                assert_eq!(Authentify::new_with_config(config).err(), Some(error));
+            }
+            assert_eq!(ink::env::test::recorded_events().count(), 0);
+
             let authentify = Authentify::new();
+            assert_eq!(authentify.get_config(), defaults);
+            assert!(defaults.validate().is_ok());
+        }
+    }
 
-            // Try to verify non-existent session
-            let result = authentify.verify_session(String::from("non_existent_session"));
-            assert_eq!(result, Err(Error::SessionNotFound));
+    // ========================================
+    // E2E TESTS
+    // ========================================
 
-            // Try to revoke non-existent session
-            let mut authentify = Authentify::new();
-            let result = authentify.revoke_session(String::from("non_existent_session"));
-            assert_eq!(result, Err(Error::SessionNotFound));
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::ContractsBackend;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn two_factor_failures_persist<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            let alice = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let password_hash = String::from("valid_password_hash");
+            let seed = [42u8; 32];
+            let mut anchor = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&seed, &mut anchor);
+
+            let mut constructor = AuthentifyRef::new();
+            let contract = client
+                .instantiate("authentify-contract", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Authentify>();
+
+            client
+                .call(&ink_e2e::alice(), &call_builder.set_direct_registration(true))
+                .submit()
+                .await
+                .expect("set_direct_registration failed");
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.register_identity(
+                        String::from("alice"),
+                        password_hash.clone(),
+                        String::from("social_hash"),
+                        String::from("google"),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("register_identity failed");
+            client
+                .call(&ink_e2e::alice(), &call_builder.enable_two_factor(anchor))
+                .submit()
+                .await
+                .expect("enable_two_factor failed");
+
+            // The pending login survives the transaction
+            let login = client
+                .call(&ink_e2e::alice(), &call_builder.authenticate(String::from("alice"), password_hash))
+                .submit()
+                .await
+                .expect("authenticate failed");
+            assert!(matches!(
+                login.return_value(),
+                Ok(LoginOutcome::TwoFactorRequired { account, .. }) if account == alice
+            ));
+            let pending = client
+                .call(&ink_e2e::alice(), &call_builder.get_pending_login(alice))
+                .dry_run()
+                .await?;
+            assert!(pending.return_value().is_some());
+
+            // A wrong code is committed as a failed attempt
+            let result = client
+                .call(&ink_e2e::alice(), &call_builder.verify_two_factor(String::from("alice"), [0u8; 32]))
+                .submit()
+                .await
+                .expect("verify_two_factor failed");
+            assert!(matches!(
+                result.return_value(),
+                Ok(LoginOutcome::Failed { reason: LoginFailureReason::BadCredential, .. })
+            ));
+            let stats = client
+                .call(&ink_e2e::alice(), &call_builder.get_stats())
+                .dry_run()
+                .await?;
+            assert_eq!(stats.return_value().failed_logins, 1);
+
+            let result = client
+                .call(&ink_e2e::alice(), &call_builder.verify_two_factor(String::from("alice"), seed))
+                .submit()
+                .await
+                .expect("verify_two_factor failed");
+            assert_eq!(result.return_value(), Ok(LoginOutcome::Success(alice)));
+
+            Ok(())
         }
 
-        #[ink::test]
-        fn test_constructor_with_config() {
-            let authentify = Authentify::new_with_config(3, 600000);
-            assert_eq!(authentify.get_max_failed_attempts(), 3);
-            assert_eq!(authentify.get_lockout_duration(), 600000);
-            assert_eq!(authentify.get_total_users(), 0);
-            assert_eq!(authentify.get_active_sessions(), 0);
+        #[ink_e2e::test]
+        async fn failed_logins_persist<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            let alice = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let mut constructor = AuthentifyRef::new();
+            let contract = client
+                .instantiate("authentify-contract", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Authentify>();
+
+            client
+                .call(&ink_e2e::alice(), &call_builder.set_direct_registration(true))
+                .submit()
+                .await
+                .expect("set_direct_registration failed");
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.register_identity(
+                        String::from("alice"),
+                        String::from("valid_password_hash"),
+                        String::from("social_hash"),
+                        String::from("google"),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("register_identity failed");
+
+            // A wrong password is not reverted: the attempt, the stats and the event stay
+            let login = client
+                .call(&ink_e2e::alice(), &call_builder.authenticate(String::from("alice"), String::from("wrong_hash")))
+                .submit()
+                .await
+                .expect("authenticate failed");
+            assert!(login.contains_event("Contracts", "ContractEmitted"));
+            assert_eq!(
+                login.return_value(),
+                Ok(LoginOutcome::Failed {
+                    reason: LoginFailureReason::BadCredential,
+                    remaining_attempts: DEFAULT_MAX_FAILED_ATTEMPTS - 1,
+                })
+            );
+
+            let identity = client
+                .call(&ink_e2e::alice(), &call_builder.get_full_identity(alice))
+                .dry_run()
+                .await?;
+            assert_eq!(identity.return_value().unwrap().failed_attempts, 1);
+            let stats = client
+                .call(&ink_e2e::alice(), &call_builder.get_stats())
+                .dry_run()
+                .await?;
+            assert_eq!(stats.return_value().failed_logins, 1);
+
+            // So is the lockout, together with its audit entries
+            for _ in 1..DEFAULT_MAX_FAILED_ATTEMPTS {
+                client
+                    .call(&ink_e2e::alice(), &call_builder.authenticate(String::from("alice"), String::from("wrong_hash")))
+                    .submit()
+                    .await
+                    .expect("authenticate failed");
+            }
+            let log = client
+                .call(&ink_e2e::alice(), &call_builder.get_audit_log(alice, 2))
+                .dry_run()
+                .await?;
+            let actions: Vec<AuditAction> = log.return_value().into_iter().map(|entry| entry.action).collect();
+            assert_eq!(actions, vec![
+                AuditAction::LoginFailed(LoginFailureReason::BadCredential),
+                AuditAction::AccountLocked,
+            ]);
+            let identity = client
+                .call(&ink_e2e::alice(), &call_builder.get_full_identity(alice))
+                .dry_run()
+                .await?;
+            assert!(identity.return_value().unwrap().is_locked);
+
+            Ok(())
         }
     }
-}
\ No newline at end of file
+}
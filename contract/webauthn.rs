@@ -0,0 +1,421 @@
+//! WebAuthn helpers used by the passkey registry
+//!
+//! Parses COSE EC2 public keys and authenticator data, checks clientDataJSON
+//! and verifies ES256 (ECDSA P-256 / SHA-256) assertion signatures.
+
+use ink::prelude::vec::Vec;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+
+/// Minimum authenticator data length: rpIdHash (32) + flags (1) + signCount (4)
+pub const AUTH_DATA_MIN_LEN: usize = 37;
+
+/// "User present" bit of the authenticator data flags
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+/// Start of a serialized assertion clientDataJSON, up to the challenge value
+const CLIENT_DATA_GET_PREFIX: &[u8] = br#"{"type":"webauthn.get","challenge":""#;
+
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// COSE key labels and values for an ES256 EC2 key
+const COSE_KTY: i64 = 1;
+const COSE_ALG: i64 = 3;
+const COSE_CRV: i64 = -1;
+const COSE_X: i64 = -2;
+const COSE_Y: i64 = -3;
+const COSE_KTY_EC2: i64 = 2;
+const COSE_ALG_ES256: i64 = -7;
+const COSE_CRV_P256: i64 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WebAuthnError {
+    /// COSE key is malformed or not an ES256 P-256 key
+    InvalidPublicKey,
+    /// Authenticator data is too short
+    InvalidAuthenticatorData,
+    /// Authenticator did not report user presence
+    UserNotPresent,
+    /// Signature is malformed or does not verify
+    InvalidSignature,
+    /// clientDataJSON is not a `webauthn.get` assertion
+    InvalidClientData,
+    /// clientDataJSON carries a different challenge
+    ChallengeMismatch,
+}
+
+/// Fixed-size prefix of WebAuthn authenticator data
+#[derive(Debug, PartialEq, Eq)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: u8,
+    pub sign_count: u32,
+}
+
+/// Parse the rpIdHash, flags and signature counter of authenticator data
+pub fn parse_authenticator_data(data: &[u8]) -> Result<AuthenticatorData, WebAuthnError> {
+    if data.len() < AUTH_DATA_MIN_LEN {
+        return Err(WebAuthnError::InvalidAuthenticatorData);
+    }
+
+    let mut rp_id_hash = [0u8; 32];
+    rp_id_hash.copy_from_slice(&data[..32]);
+    let flags = data[32];
+
+    if flags & FLAG_USER_PRESENT == 0 {
+        return Err(WebAuthnError::UserNotPresent);
+    }
+
+    let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+    Ok(AuthenticatorData { rp_id_hash, flags, sign_count })
+}
+
+/// Check that clientDataJSON is a `webauthn.get` assertion over `challenge`
+///
+/// Follows the WebAuthn limited verification algorithm: browsers serialize
+/// `type` and `challenge` first, so both are matched as an exact prefix and
+/// the remaining members (origin, crossOrigin, ...) are left to the caller.
+pub fn check_client_data(client_data_json: &[u8], challenge: &[u8; 32]) -> Result<(), WebAuthnError> {
+    let rest = client_data_json
+        .strip_prefix(CLIENT_DATA_GET_PREFIX)
+        .ok_or(WebAuthnError::InvalidClientData)?;
+
+    let mut expected = base64url_encode(challenge);
+    expected.push(b'"');
+
+    if !rest.starts_with(&expected) {
+        return Err(WebAuthnError::ChallengeMismatch);
+    }
+
+    Ok(())
+}
+
+/// Unpadded base64url, as used for the clientDataJSON challenge
+fn base64url_encode(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity((data.len() * 4).div_ceil(3));
+
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | u32::from(*byte) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            output.push(BASE64URL_ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize]);
+        }
+    }
+
+    output
+}
+
+/// Parse a COSE_Key map holding an ES256 P-256 key into a verifying key
+pub fn parse_cose_p256_key(cose_key: &[u8]) -> Result<VerifyingKey, WebAuthnError> {
+    let mut reader = CborReader { data: cose_key, pos: 0 };
+    let (major, entries) = reader.read_head()?;
+
+    if major != MAJOR_MAP {
+        return Err(WebAuthnError::InvalidPublicKey);
+    }
+
+    let (mut kty, mut alg, mut crv) = (None, None, None);
+    let (mut x, mut y) = (None, None);
+
+    for _ in 0..entries {
+        let label = match reader.read_value()? {
+            CborValue::Int(label) => label,
+            CborValue::Bytes(_) => return Err(WebAuthnError::InvalidPublicKey),
+        };
+
+        match (label, reader.read_value()?) {
+            (COSE_KTY, CborValue::Int(value)) => kty = Some(value),
+            (COSE_ALG, CborValue::Int(value)) => alg = Some(value),
+            (COSE_CRV, CborValue::Int(value)) => crv = Some(value),
+            (COSE_X, CborValue::Bytes(value)) if value.len() == 32 => x = Some(value),
+            (COSE_Y, CborValue::Bytes(value)) if value.len() == 32 => y = Some(value),
+            (COSE_KTY | COSE_ALG | COSE_CRV | COSE_X | COSE_Y, _) => {
+                return Err(WebAuthnError::InvalidPublicKey)
+            }
+            // Ignore labels we do not use
+            _ => {}
+        }
+    }
+
+    if reader.pos != cose_key.len()
+        || kty != Some(COSE_KTY_EC2)
+        || alg != Some(COSE_ALG_ES256)
+        || crv != Some(COSE_CRV_P256)
+    {
+        return Err(WebAuthnError::InvalidPublicKey);
+    }
+
+    let (Some(x), Some(y)) = (x, y) else {
+        return Err(WebAuthnError::InvalidPublicKey);
+    };
+
+    let mut sec1 = [0u8; 65];
+    sec1[0] = 0x04;
+    sec1[1..33].copy_from_slice(x);
+    sec1[33..].copy_from_slice(y);
+
+    VerifyingKey::from_sec1_bytes(&sec1).map_err(|_| WebAuthnError::InvalidPublicKey)
+}
+
+/// Verify a DER-encoded ES256 assertion signature over
+/// `authenticator_data || client_data_hash`
+pub fn verify_assertion(
+    key: &VerifyingKey,
+    authenticator_data: &[u8],
+    client_data_hash: &[u8; 32],
+    der_signature: &[u8],
+) -> Result<(), WebAuthnError> {
+    let signature =
+        Signature::from_der(der_signature).map_err(|_| WebAuthnError::InvalidSignature)?;
+
+    let mut signed_data = Vec::with_capacity(authenticator_data.len() + 32);
+    signed_data.extend_from_slice(authenticator_data);
+    signed_data.extend_from_slice(client_data_hash);
+
+    key.verify(&signed_data, &signature)
+        .map_err(|_| WebAuthnError::InvalidSignature)
+}
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_MAP: u8 = 5;
+
+/// The subset of CBOR values that appear in a COSE EC2 key
+enum CborValue<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+}
+
+/// Minimal CBOR reader, just enough for COSE keys
+struct CborReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], WebAuthnError> {
+        let end = self.pos.checked_add(len).ok_or(WebAuthnError::InvalidPublicKey)?;
+        let bytes = self.data.get(self.pos..end).ok_or(WebAuthnError::InvalidPublicKey)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_head(&mut self) -> Result<(u8, u64), WebAuthnError> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let argument = match initial & 0x1f {
+            info @ 0..=23 => u64::from(info),
+            24 => u64::from(self.take(1)?[0]),
+            25 => {
+                let bytes = self.take(2)?;
+                u64::from(u16::from_be_bytes([bytes[0], bytes[1]]))
+            }
+            26 => {
+                let bytes = self.take(4)?;
+                u64::from(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            _ => return Err(WebAuthnError::InvalidPublicKey),
+        };
+
+        Ok((major, argument))
+    }
+
+    fn read_value(&mut self) -> Result<CborValue<'a>, WebAuthnError> {
+        match self.read_head()? {
+            (MAJOR_UNSIGNED, value) => Ok(CborValue::Int(value as i64)),
+            (MAJOR_NEGATIVE, value) => Ok(CborValue::Int(-1 - value as i64)),
+            (MAJOR_BYTES, len) => Ok(CborValue::Bytes(self.take(len as usize)?)),
+            _ => Err(WebAuthnError::InvalidPublicKey),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_vectors {
+    //! Fixed ES256 vectors generated from a throwaway P-256 key
+
+    use ink::prelude::vec::Vec;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::{Signature, SigningKey};
+
+    /// SHA-256 of "authentify.example"
+    pub const RP_ID_HASH: &str = "a69625071caa18af5dd1a1f34654234b6630e37b4107c524247b3536a7e02d9f";
+    /// Authenticator data with flags UP|UV and signCount 1
+    pub const AUTH_DATA_1: &str =
+        "a69625071caa18af5dd1a1f34654234b6630e37b4107c524247b3536a7e02d9f0500000001";
+    pub const SIGNATURE_1: &str = "304602210088450b001592c1c7aa5d67c8f7f010f6f71bb5f5bde42cec53f4d0d629986a53022100b43c5a135de398c9e3e43a655bb7b1818a53a6cf8e5c34ffbc8ae9b8770b3b5c";
+    /// Authenticator data with flags UP|UV and signCount 2
+    pub const AUTH_DATA_2: &str =
+        "a69625071caa18af5dd1a1f34654234b6630e37b4107c524247b3536a7e02d9f0500000002";
+    pub const SIGNATURE_2: &str = "304502207c690162d10d546b2f9a1187078eaa01354171c08d49ae3fba715ad462a268b1022100eaa1917770177425fccf87b512469a8bfb36cf8275dc4f2f1ec15939e8000e80";
+    pub const CLIENT_DATA_JSON: &[u8] = br#"{"type":"webauthn.get","challenge":"dGVzdC1jaGFsbGVuZ2U","origin":"https://authentify.example"}"#;
+
+    /// Test key as COSE_Key {1: 2, 3: -7, -1: 1, -2: x, -3: y}
+    pub fn cose_key() -> Vec<u8> {
+        let mut key = Vec::new();
+        key.extend_from_slice(&[0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20]);
+        key.extend_from_slice(&hex("bd7c73b88b2e9b4ceda62022b2da8be13193a5b56edc26e7df7842e24cd0b5eb"));
+        key.extend_from_slice(&[0x22, 0x58, 0x20]);
+        key.extend_from_slice(&hex("0605ada7bda83ac6a2b80d7e314040fa47ff16b83bac85cedb014451bb7ce71a"));
+        key
+    }
+
+    pub fn hex(value: &str) -> Vec<u8> {
+        (0..value.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    pub fn rp_id_hash() -> [u8; 32] {
+        let mut output = [0u8; 32];
+        output.copy_from_slice(&hex(RP_ID_HASH));
+        output
+    }
+
+    pub fn client_data_hash() -> [u8; 32] {
+        let mut output = [0u8; 32];
+        ink::env::hash_bytes::<ink::env::hash::Sha2x256>(CLIENT_DATA_JSON, &mut output);
+        output
+    }
+
+    /// Throwaway key for signing assertions over contract-issued challenges
+    pub fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    /// `signing_key` as COSE_Key
+    pub fn signing_cose_key() -> Vec<u8> {
+        let point = signing_key().verifying_key().to_encoded_point(false);
+        let mut key = Vec::new();
+        key.extend_from_slice(&[0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20]);
+        key.extend_from_slice(point.x().unwrap());
+        key.extend_from_slice(&[0x22, 0x58, 0x20]);
+        key.extend_from_slice(point.y().unwrap());
+        key
+    }
+
+    /// Authenticator data for `RP_ID_HASH` with flags UP|UV
+    pub fn auth_data(sign_count: u32) -> Vec<u8> {
+        let mut data = hex(RP_ID_HASH);
+        data.push(0x05);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        data
+    }
+
+    /// clientDataJSON of an assertion over `challenge`
+    pub fn client_data_json(challenge: &[u8; 32]) -> Vec<u8> {
+        let mut json = super::CLIENT_DATA_GET_PREFIX.to_vec();
+        json.extend_from_slice(&super::base64url_encode(challenge));
+        json.extend_from_slice(br#"","origin":"https://authentify.example"}"#);
+        json
+    }
+
+    /// DER signature of `signing_key` over `authenticator_data || SHA-256(client_data_json)`
+    pub fn sign_assertion(authenticator_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+        let mut client_data_hash = [0u8; 32];
+        ink::env::hash_bytes::<ink::env::hash::Sha2x256>(client_data_json, &mut client_data_hash);
+
+        let mut signed_data = authenticator_data.to_vec();
+        signed_data.extend_from_slice(&client_data_hash);
+        let signature: Signature = signing_key().sign(&signed_data);
+        signature.to_der().as_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_vectors::*;
+    use super::*;
+
+    #[test]
+    fn parses_authenticator_data() {
+        let data = parse_authenticator_data(&hex(AUTH_DATA_1)).unwrap();
+        assert_eq!(data.sign_count, 1);
+        assert_eq!(data.flags, 0x05);
+        assert_eq!(data.rp_id_hash, rp_id_hash());
+
+        assert_eq!(
+            parse_authenticator_data(&[0u8; 36]),
+            Err(WebAuthnError::InvalidAuthenticatorData)
+        );
+        assert_eq!(
+            parse_authenticator_data(&[0u8; 37]),
+            Err(WebAuthnError::UserNotPresent)
+        );
+    }
+
+    #[test]
+    fn encodes_base64url_without_padding() {
+        assert_eq!(base64url_encode(b"test-challenge"), b"dGVzdC1jaGFsbGVuZ2U");
+        assert_eq!(base64url_encode(&[0xfb, 0xff]), b"-_8");
+        assert_eq!(base64url_encode(&[0u8; 32]).len(), 43);
+    }
+
+    #[test]
+    fn checks_client_data() {
+        let challenge = [9u8; 32];
+        let json = client_data_json(&challenge);
+        assert!(check_client_data(&json, &challenge).is_ok());
+
+        assert_eq!(check_client_data(&json, &[8u8; 32]), Err(WebAuthnError::ChallengeMismatch));
+
+        // Registration ceremonies are not assertions
+        let create = [br#"{"type":"webauthn.create""#.as_slice(), &json[22..]].concat();
+        assert_eq!(check_client_data(&create, &challenge), Err(WebAuthnError::InvalidClientData));
+
+        // The challenge must end where the encoding ends
+        let mut extended = json.clone();
+        extended.insert(CLIENT_DATA_GET_PREFIX.len() + 43, b'A');
+        assert_eq!(check_client_data(&extended, &challenge), Err(WebAuthnError::ChallengeMismatch));
+    }
+
+    #[test]
+    fn rejects_malformed_cose_keys() {
+        let mut key = cose_key();
+        assert!(parse_cose_p256_key(&key).is_ok());
+
+        // Wrong algorithm (-8 = EdDSA)
+        key[4] = 0x27;
+        assert_eq!(parse_cose_p256_key(&key).err(), Some(WebAuthnError::InvalidPublicKey));
+
+        // Truncated
+        let key = cose_key();
+        assert_eq!(
+            parse_cose_p256_key(&key[..key.len() - 1]).err(),
+            Some(WebAuthnError::InvalidPublicKey)
+        );
+
+        // Point not on the curve
+        let mut key = cose_key();
+        let last = key.len() - 1;
+        key[last] ^= 0x01;
+        assert_eq!(parse_cose_p256_key(&key).err(), Some(WebAuthnError::InvalidPublicKey));
+    }
+
+    #[test]
+    fn verifies_es256_assertion() {
+        let key = parse_cose_p256_key(&cose_key()).unwrap();
+        let auth_data = hex(AUTH_DATA_1);
+        let signature = hex(SIGNATURE_1);
+
+        assert!(verify_assertion(&key, &auth_data, &client_data_hash(), &signature).is_ok());
+        assert!(verify_assertion(&key, &hex(AUTH_DATA_2), &client_data_hash(), &hex(SIGNATURE_2)).is_ok());
+
+        // Signature over different authenticator data
+        assert_eq!(
+            verify_assertion(&key, &hex(AUTH_DATA_2), &client_data_hash(), &signature),
+            Err(WebAuthnError::InvalidSignature)
+        );
+
+        // Malformed signature
+        assert_eq!(
+            verify_assertion(&key, &auth_data, &client_data_hash(), &signature[1..]),
+            Err(WebAuthnError::InvalidSignature)
+        );
+    }
+}
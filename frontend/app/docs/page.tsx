@@ -596,11 +596,11 @@ export function LoginForm() {
                       desc: "Authenticate via contract",
                     },
                     {
-                      name: "createSession(accountId, duration)",
+                      name: "createSession(accountId, duration, clientId?)",
                       desc: "Create on-chain session",
                     },
                     {
-                      name: "verifySession(sessionId)",
+                      name: "verifySession(sessionId, clientId?)",
                       desc: "Verify session",
                     },
                     {
@@ -11,6 +11,7 @@ import {
   IdentityInfo,
   OnchainAuthSession,
   ContractResult,
+  LoginOutcome,
 } from "./types";
 import { CONTRACT_METHODS, DEFAULT_CONFIG } from "./constants";
 import { AuthentifyError, parseContractResult } from "./utils";
@@ -176,7 +177,7 @@ export class ContractClient {
   public async authenticate(
     username: string,
     passwordHash: string
-  ): Promise<string | null> {
+  ): Promise<LoginOutcome | null> {
     await this.ensureInitialized();
     const gasLimit = this.makeGas();
     const result = await this.contract!.query[CONTRACT_METHODS.AUTHENTICATE](
@@ -187,7 +188,7 @@ export class ContractClient {
     );
     if (result.result.isOk && result.output) {
       return parseContractResult(
-        result.output.toHuman() as unknown as ContractResult<string>
+        result.output.toHuman() as unknown as ContractResult<LoginOutcome>
       );
     }
     return null;
@@ -196,7 +197,8 @@ export class ContractClient {
   public async createSession(
     accountId: string,
     sessionId: string,
-    durationMs: number
+    durationMs: number,
+    clientId: number | null = null
   ): Promise<OnchainAuthSession | null> {
     await this.ensureInitialized();
     this.ensureSigner();
@@ -206,13 +208,15 @@ export class ContractClient {
       value,
       accountId,
       sessionId,
-      durationMs
+      durationMs,
+      clientId
     );
     await this.contract!.tx[CONTRACT_METHODS.CREATE_SESSION](
       { gasLimit, storageDepositLimit: null, value },
       accountId,
       sessionId,
-      durationMs
+      durationMs,
+      clientId
     ).signAndSend(this.signerAddress!, { signer: this.signerInjector!.signer });
     return {
       sessionId,
@@ -222,13 +226,17 @@ export class ContractClient {
     };
   }
 
-  public async verifySession(sessionId: string): Promise<string | null> {
+  public async verifySession(
+    sessionId: string,
+    clientId: number | null = null
+  ): Promise<string | null> {
     await this.ensureInitialized();
     const gasLimit = this.makeGas();
     const result = await this.contract!.query[CONTRACT_METHODS.VERIFY_SESSION](
       this.caller(),
       { gasLimit, storageDepositLimit: null },
-      sessionId
+      sessionId,
+      clientId
     );
     if (result.result.isOk && result.output) {
       return parseContractResult(
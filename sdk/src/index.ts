@@ -17,6 +17,7 @@ export type {
   WalletAccount,
   ContractIdentity,
   AuthStats,
+  LoginOutcome,
 } from './types';
 
 // Utility exports
@@ -33,6 +34,8 @@ export {
   isBrowser,
   safeJSONParse,
   withTimeout,
+  computeAttributeCommitment,
+  verifyAttributeCommitment,
 } from './utils';
 
 // React integration exports (optional)
@@ -119,7 +119,8 @@ export class AuthentifySDK {
 
   /**
    * Authenticate via on-chain contract using username + password.
-   * Returns the accountId if credentials are valid, else null.
+   * Returns the accountId if the login completed, else null (including when
+   * the account still needs its second factor).
    */
   async contractAuthenticate(username: string, password: string): Promise<string | null> {
     if (!this.contractClient) {
@@ -127,19 +128,24 @@ export class AuthentifySDK {
     }
     await this.contractClient.initialize();
     const pwdHash = await hashPassword(password);
-    return this.contractClient.authenticate(username, pwdHash);
+    const outcome = await this.contractClient.authenticate(username, pwdHash);
+    return outcome && 'Success' in outcome ? outcome.Success : null;
   }
 
   /**
-   * Create an on-chain session for a given account.
+   * Create an on-chain session for a given account, optionally scoped to a client.
    */
-  async contractCreateSession(accountId: string, durationMs = 24 * 60 * 60 * 1000) {
+  async contractCreateSession(
+    accountId: string,
+    durationMs = 24 * 60 * 60 * 1000,
+    clientId: number | null = null
+  ) {
     if (!this.contractClient) {
       throw new AuthentifyError('Contract client not initialized', 'NO_CONTRACT_CLIENT');
     }
     await this.contractClient.initialize();
     const sessionId = `sess_${Date.now()}_${Math.random().toString(36).slice(2, 8)}`;
-    return this.contractClient.createSession(accountId, sessionId, durationMs);
+    return this.contractClient.createSession(accountId, sessionId, durationMs, clientId);
   }
 
   async contractRevokeSession(sessionId: string): Promise<boolean> {
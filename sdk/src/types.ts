@@ -42,6 +42,12 @@ export interface AuthSession {
   token_type: string;
 }
 
+// Outcome of an on-chain login attempt (matches contract LoginOutcome)
+export type LoginOutcome =
+  | { Success: string }
+  | { TwoFactorRequired: { account: string; expires_at: number } }
+  | { Failed: { reason: string; remaining_attempts: number } };
+
 // On-chain session (for contract-based auth flows)
 export interface OnchainAuthSession {
   sessionId: string;
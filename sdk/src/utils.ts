@@ -1,5 +1,7 @@
 import { AuthentifyConfig, ContractResult } from './types';
 import bcrypt from 'bcryptjs';
+import { compactAddLength, stringToU8a, u8aConcat, u8aEq, u8aToU8a } from '@polkadot/util';
+import { blake2AsU8a, decodeAddress } from '@polkadot/util-crypto';
 
 // Custom error class used throughout the SDK
 export class AuthentifyError extends Error {
@@ -21,6 +23,38 @@ export function generateSessionId(): string {
   return `sess_${Date.now()}_${Math.random().toString(36).slice(2, 10)}`;
 }
 
+// ----------- Attribute Commitments -----------
+// Same as the contract's `compute_attribute_commitment`: Blake2x256 of the SCALE
+// encoding of (account, key, value, salt). Always compute locally; sending the
+// value and salt to a node, even for a dry-run, discloses them.
+export function computeAttributeCommitment(
+  accountId: string,
+  key: string,
+  value: string,
+  salt: Uint8Array
+): Uint8Array {
+  return blake2AsU8a(
+    u8aConcat(
+      decodeAddress(accountId),
+      compactAddLength(stringToU8a(key)),
+      compactAddLength(stringToU8a(value)),
+      salt
+    ),
+    256
+  );
+}
+
+// Check a disclosed value against the commitment returned by `get_attribute`
+export function verifyAttributeCommitment(
+  commitment: Uint8Array | string,
+  accountId: string,
+  key: string,
+  value: string,
+  salt: Uint8Array
+): boolean {
+  return u8aEq(u8aToU8a(commitment), computeAttributeCommitment(accountId, key, value, salt));
+}
+
 export function validateUsername(username: string): void {
   if (!username) throw new AuthentifyError('Username required', 'INVALID_USERNAME');
   if (username.length < 3) throw new AuthentifyError('Username too short', 'INVALID_USERNAME');